@@ -0,0 +1,360 @@
+use shuuro::{
+    Color, Move, Square,
+    attacks::Attacks,
+    bitboard::BitBoard,
+    position::{Board, Placement, Play, Rules, Sfen},
+};
+use std::{
+    fmt::Display,
+    fs::File,
+    hash::Hash,
+    io::{self, BufWriter, Write},
+    sync::Arc,
+    thread,
+    time::Duration,
+};
+
+use crate::{
+    engine::{Engine, EngineDefs, STACK_SIZE, SearchLimits},
+    game_record::{Annotation, GameRecord, GameResult, RecordError},
+    nnue::{Network, Nnue},
+};
+
+// Every position of a game is searched with the same limits. A move's mark
+// comes from how much the score drops for the mover between the position
+// before and after it, and marked moves get the engine's line as variation.
+
+const MATE_SCORE: i32 = 32000;
+// Longest variation written for a marked move.
+const MAX_VARIATION_PLIES: i32 = 8;
+
+#[derive(Clone, Debug)]
+pub struct AnnotateOptions {
+    pub limits: SearchLimits,
+    pub threads: usize,
+    pub input: String,
+    pub output: String,
+    /// Score drops in centipawns from which a move is marked.
+    pub inaccuracy: i32,
+    pub mistake: i32,
+    pub blunder: i32,
+}
+
+impl Default for AnnotateOptions {
+    fn default() -> Self {
+        Self {
+            limits: SearchLimits {
+                depth: Some(3),
+                ..Default::default()
+            },
+            threads: 1,
+            input: String::new(),
+            output: String::from("annotated.txt"),
+            inaccuracy: 50,
+            mistake: 100,
+            blunder: 300,
+        }
+    }
+}
+
+impl AnnotateOptions {
+    /// Parses `input <file> output <file> depth <n> nodes <n> movetime <ms>
+    /// threads <n> inaccuracy <cp> mistake <cp> blunder <cp>`.
+    pub fn parse(args: &str) -> Self {
+        let mut options = Self::default();
+        let mut tokens = args.split_whitespace();
+        while let Some(token) = tokens.next() {
+            let Some(value) = tokens.next() else {
+                break;
+            };
+            match token {
+                "input" => options.input = value.to_string(),
+                "output" => options.output = value.to_string(),
+                "depth" => {
+                    options.limits.depth = value.parse().ok();
+                }
+                "nodes" => {
+                    options.limits.nodes = value.parse().ok();
+                    options.limits.depth = None;
+                }
+                "movetime" => {
+                    options.limits.movetime = value.parse().ok().map(Duration::from_millis);
+                    options.limits.depth = None;
+                }
+                "threads" => options.threads = value.parse().unwrap_or(1).max(1),
+                "inaccuracy" => options.inaccuracy = value.parse().unwrap_or(options.inaccuracy),
+                "mistake" => options.mistake = value.parse().unwrap_or(options.mistake),
+                "blunder" => options.blunder = value.parse().unwrap_or(options.blunder),
+                _ => (),
+            }
+        }
+        options
+    }
+
+    fn annotation(&self, drop: i32) -> Option<Annotation> {
+        if drop >= self.blunder {
+            Some(Annotation::Blunder)
+        } else if drop >= self.mistake {
+            Some(Annotation::Mistake)
+        } else if drop >= self.inaccuracy {
+            Some(Annotation::Inaccuracy)
+        } else {
+            None
+        }
+    }
+}
+
+/// Marked moves of all annotated games.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AnnotateStats {
+    pub games: usize,
+    pub inaccuracies: usize,
+    pub mistakes: usize,
+    pub blunders: usize,
+}
+
+impl Display for AnnotateStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{} games, {} inaccuracies, {} mistakes, {} blunders",
+            self.games, self.inaccuracies, self.mistakes, self.blunders
+        )
+    }
+}
+
+/// Annotates every game in `options.input` and writes them to
+/// `options.output` in input order. Games that don't replay on this board
+/// are reported and left out.
+pub fn run<E, S, B, A, P, D, const FILE: usize, const BITBOARD_SIZE: usize, const RANK: usize>(
+    options: &AnnotateOptions,
+    network: Option<Arc<Network>>,
+) -> io::Result<AnnotateStats>
+where
+    E: Engine<S, B, A, P, D, FILE, BITBOARD_SIZE, RANK>,
+    S: Square + Hash + Send + 'static,
+    B: BitBoard<S>,
+    A: Attacks<S, B>,
+    P: Sized
+        + Display
+        + Clone
+        + Board<S, B, A>
+        + Sfen<S, B, A>
+        + Placement<S, B, A>
+        + Play<S, B, A>
+        + Rules<S, B, A>
+        + Send
+        + 'static,
+    D: EngineDefs<S, B, FILE>,
+{
+    E::init();
+    let text = std::fs::read_to_string(&options.input)?;
+    let games = GameRecord::parse_all(&text)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))?;
+
+    let mut annotated: Vec<(usize, Result<GameRecord, RecordError>)> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..options.threads)
+            .map(|t| {
+                let network = network.clone();
+                let games = &games;
+                thread::Builder::new()
+                    .stack_size(STACK_SIZE)
+                    .spawn_scoped(scope, move || {
+                        let mut engine = E::new();
+                        *engine.nnue_mut() = network.map(Nnue::new);
+                        (t..games.len())
+                            .step_by(options.threads)
+                            .map(|index| {
+                                let game = annotate(&mut engine, options, games[index].clone());
+                                println!("info string annotate game {}", index + 1);
+                                (index, game)
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                    .expect("failed to spawn annotate thread")
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|h| h.join().expect("annotate thread panicked"))
+            .collect()
+    });
+    annotated.sort_by_key(|(index, _)| *index);
+
+    let mut out = BufWriter::new(File::create(&options.output)?);
+    let mut stats = AnnotateStats::default();
+    for (index, game) in annotated {
+        let game = match game {
+            Ok(game) => game,
+            Err(err) => {
+                println!("info string annotate skipped game {}: {err}", index + 1);
+                continue;
+            }
+        };
+        for annotation in game.fight_moves.iter().filter_map(|m| m.annotation) {
+            match annotation {
+                Annotation::Inaccuracy => stats.inaccuracies += 1,
+                Annotation::Mistake => stats.mistakes += 1,
+                Annotation::Blunder => stats.blunders += 1,
+            }
+        }
+        stats.games += 1;
+        writeln!(out, "{game}")?;
+    }
+    out.flush()?;
+    Ok(stats)
+}
+
+/// Searches every fight position of `game`, setting the evaluation after
+/// each move and marking the moves that lose too much.
+pub fn annotate<
+    E,
+    S,
+    B,
+    A,
+    P,
+    D,
+    const FILE: usize,
+    const BITBOARD_SIZE: usize,
+    const RANK: usize,
+>(
+    engine: &mut E,
+    options: &AnnotateOptions,
+    mut game: GameRecord,
+) -> Result<GameRecord, RecordError>
+where
+    E: Engine<S, B, A, P, D, FILE, BITBOARD_SIZE, RANK>,
+    S: Square + Hash + Send + 'static,
+    B: BitBoard<S>,
+    A: Attacks<S, B>,
+    P: Sized
+        + Display
+        + Clone
+        + Board<S, B, A>
+        + Sfen<S, B, A>
+        + Placement<S, B, A>
+        + Play<S, B, A>
+        + Rules<S, B, A>
+        + Send
+        + 'static,
+    D: EngineDefs<S, B, FILE>,
+{
+    game.replay::<S, B, A, P>()?;
+    let mut position = P::new();
+    position.update_variant(game.variant);
+    position
+        .set_sfen(&game.fight_sfen)
+        .map_err(|_| RecordError::BadSfen(game.fight_sfen.clone()))?;
+
+    // The played move is searched one ply shallower than the position
+    // before it, so both scores look equally far ahead.
+    let mut reduced = options.limits.clone();
+    reduced.depth = reduced.depth.map(|depth| (depth - 1).max(1));
+    for fight_move in game.fight_moves.iter_mut() {
+        let before = position.clone();
+        let stm = position.side_to_move();
+        // `replay` already checked the moves.
+        let Some(mv) = Move::<S>::from_sfen(&fight_move.mv) else {
+            break;
+        };
+        let (score, best_move, depth) = evaluate(engine, &position, &options.limits);
+        let played = position.make_move(mv.clone());
+        let next_score = match (played, &best_move) {
+            (Err(_), _) => 0,
+            (Ok(_), Some(best)) if *best == mv => score,
+            (Ok(_), _) => evaluate(engine, &position, &reduced).0,
+        };
+        fight_move.eval = Some(next_score);
+        fight_move.annotation = None;
+        fight_move.variation.clear();
+
+        let drop = match stm {
+            Color::White => score - next_score,
+            _ => next_score - score,
+        };
+        if let Some(best) = best_move.filter(|best| *best != mv)
+            && let Some(annotation) = options.annotation(drop)
+        {
+            fight_move.annotation = Some(annotation);
+            fight_move.comment = Some(format!("{annotation:?}. Best was {best}"));
+            fight_move.variation = line(engine, &before, best, depth, &options.limits);
+        }
+    }
+    Ok(game)
+}
+
+/// Score from White's point of view, best move and depth reached.
+fn evaluate<E, S, B, A, P, D, const FILE: usize, const BITBOARD_SIZE: usize, const RANK: usize>(
+    engine: &mut E,
+    position: &P,
+    limits: &SearchLimits,
+) -> (i32, Option<Move<S>>, i32)
+where
+    E: Engine<S, B, A, P, D, FILE, BITBOARD_SIZE, RANK>,
+    S: Square + Hash + Send + 'static,
+    B: BitBoard<S>,
+    A: Attacks<S, B>,
+    P: Sized
+        + Display
+        + Clone
+        + Board<S, B, A>
+        + Sfen<S, B, A>
+        + Placement<S, B, A>
+        + Play<S, B, A>
+        + Rules<S, B, A>
+        + Send
+        + 'static,
+    D: EngineDefs<S, B, FILE>,
+{
+    let result = match engine.game_result(position) {
+        GameResult::WhiteWins => return (MATE_SCORE, None, 0),
+        GameResult::BlackWins => return (-MATE_SCORE, None, 0),
+        GameResult::Draw => return (0, None, 0),
+        GameResult::Aborted => engine.search(position, limits.clone()),
+    };
+    let score = result.score.clamp(-MATE_SCORE, MATE_SCORE);
+    (score, result.best_move, result.depth)
+}
+
+/// `first` followed by the engine's replies, one ply shallower each move.
+fn line<E, S, B, A, P, D, const FILE: usize, const BITBOARD_SIZE: usize, const RANK: usize>(
+    engine: &mut E,
+    position: &P,
+    first: Move<S>,
+    depth: i32,
+    limits: &SearchLimits,
+) -> Vec<String>
+where
+    E: Engine<S, B, A, P, D, FILE, BITBOARD_SIZE, RANK>,
+    S: Square + Hash + Send + 'static,
+    B: BitBoard<S>,
+    A: Attacks<S, B>,
+    P: Sized
+        + Display
+        + Clone
+        + Board<S, B, A>
+        + Sfen<S, B, A>
+        + Placement<S, B, A>
+        + Play<S, B, A>
+        + Rules<S, B, A>
+        + Send
+        + 'static,
+    D: EngineDefs<S, B, FILE>,
+{
+    let mut position = position.clone();
+    let mut moves = vec![];
+    let mut mv = first;
+    for ply in 1..=depth.min(MAX_VARIATION_PLIES) {
+        moves.push(mv.to_string());
+        if position.make_move(mv).is_err() || ply == depth {
+            break;
+        }
+        let mut limits = limits.clone();
+        limits.depth = limits.depth.map(|d| (d - ply).max(1));
+        match evaluate(engine, &position, &limits) {
+            (_, Some(next), _) => mv = next,
+            _ => break,
+        }
+    }
+    moves
+}
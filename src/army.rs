@@ -0,0 +1,299 @@
+use shuuro::{
+    Color, Move, Piece, PieceType, Selection, Square, Variant,
+    attacks::Attacks,
+    bitboard::BitBoard,
+    position::{Board, Placement, Play, Rules, Sfen},
+    shuuro6::{bitboard6::BB6, square6::Square6},
+    shuuro8::{bitboard8::BB8, square8::Square8},
+    shuuro12::{bitboard12::BB12, square12::Square12},
+};
+use std::{fmt, fmt::Display, hash::Hash, str::FromStr};
+
+use crate::{
+    autoplay::{GameResult, fight, seeded_plinths},
+    engine::{Engine, EngineDefs, SearchLimits},
+    engine6::search::Defs6,
+    engine8::search::Defs8,
+    engine12::search::Defs12,
+    on_board,
+    prng::Prng,
+};
+
+/// Fight plies of a simulated game before it is adjudicated.
+pub const SIMULATION_PLIES: usize = 120;
+
+/// Static evaluation needed to adjudicate a game that reached the ply limit.
+const ADJUDICATION_MARGIN: i32 = 300;
+
+/// Purchased pieces counted per piece type, the king is always included.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Army {
+    counts: [u8; 9],
+}
+
+impl Default for Army {
+    fn default() -> Self {
+        let mut counts = [0; 9];
+        counts[PieceType::King.index()] = 1;
+        Self { counts }
+    }
+}
+
+impl Army {
+    /// Pieces `color` has bought in `selection`.
+    pub fn from_selection<S: Square>(selection: &Selection<S>, color: Color) -> Self {
+        let mut army = Self::default();
+        for piece_type in PieceType::iter().filter(|pt| *pt != PieceType::Plinth) {
+            army.counts[piece_type.index()] = selection.get(Piece { piece_type, color });
+        }
+        army.counts[PieceType::King.index()] = 1;
+        army
+    }
+
+    pub fn count(&self, piece_type: PieceType) -> u8 {
+        self.counts[piece_type.index()]
+    }
+
+    /// Hand in long sfen notation, e.g. `KQRRPP` for White.
+    pub fn to_hand(&self, color: Color) -> String {
+        let mut hand = String::new();
+        for piece_type in PieceType::iter().filter(|pt| *pt != PieceType::Plinth) {
+            let piece = Piece { piece_type, color };
+            for _ in 0..self.count(piece_type) {
+                hand.push_str(&piece.to_string());
+            }
+        }
+        hand
+    }
+
+    fn phase<S, B, D, const FILE: usize>(&self) -> i32
+    where
+        S: Square,
+        B: BitBoard<S>,
+        D: EngineDefs<S, B, FILE>,
+    {
+        (0..9)
+            .map(|index| D::phase_weight(index) * self.counts[index] as i32)
+            .sum()
+    }
+
+    /// Material of the army by the phase of both armies together: endgame
+    /// values up to the search's midgame threshold, midgame values from its
+    /// phase cap, blended in between.
+    fn material<S, B, D, const FILE: usize>(&self, phase: i32) -> i32
+    where
+        S: Square,
+        B: BitBoard<S>,
+        D: EngineDefs<S, B, FILE>,
+    {
+        let (min, max) = D::midgame_min();
+        let phase = phase.clamp(min, max) - min;
+        let range = max - min;
+        let mut score = 0;
+        for piece_type in PieceType::iter().filter(|pt| *pt != PieceType::Plinth) {
+            let count = self.count(piece_type) as i32;
+            let midgame = D::get_piece_value(piece_type);
+            let endgame = D::get_endgame_piece_value(piece_type);
+            score += count * (midgame * phase + endgame * (range - phase)) / range;
+        }
+        score
+    }
+}
+
+impl FromStr for Army {
+    type Err = ();
+
+    /// Parses piece letters in either case, e.g. `qrrbbpppp` or `AA`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut army = Self::default();
+        for c in s.chars() {
+            match PieceType::from_sfen(c) {
+                Some(PieceType::Plinth) | None => return Err(()),
+                Some(piece_type) => army.counts[piece_type.index()] += 1,
+            }
+        }
+        army.counts[PieceType::King.index()] = 1;
+        Ok(army)
+    }
+}
+
+impl Display for Army {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_hand(Color::White))
+    }
+}
+
+/// Estimates how an army does against another one in a variant. The static
+/// estimate compares material; with simulation enabled, random deployments
+/// are played out in short engine games.
+#[derive(Clone, Debug)]
+pub struct ArmyEvaluator {
+    variant: Variant,
+    games: usize,
+    seed: u64,
+    limits: SearchLimits,
+}
+
+impl ArmyEvaluator {
+    pub fn new(variant: Variant) -> Self {
+        Self {
+            variant,
+            games: 0,
+            seed: 0,
+            limits: SearchLimits::default(),
+        }
+    }
+
+    /// Plays `games` simulated games, alternating colours, searched with `limits`.
+    pub fn with_simulation(mut self, games: usize, seed: u64, limits: SearchLimits) -> Self {
+        self.games = games;
+        self.seed = seed;
+        self.limits = limits;
+        self
+    }
+
+    /// Expected score of `army` against `opponent`, between 0 and 1. The
+    /// static estimate counts as one game next to the simulated ones.
+    pub fn expected_score(&self, army: &Army, opponent: &Army) -> f64 {
+        let mut total = self.static_score(army, opponent);
+        for game in 0..self.games {
+            total += self.simulate(army, opponent, game);
+        }
+        total / (self.games + 1) as f64
+    }
+
+    /// Material difference converted to an expected score, Elo style.
+    pub fn static_score(&self, army: &Army, opponent: &Army) -> f64 {
+        let diff = match self.variant {
+            Variant::ShuuroMini | Variant::ShuuroMiniFairy => {
+                material_diff::<Square6, BB6<Square6>, Defs6, 6>(army, opponent)
+            }
+            Variant::Standard | Variant::StandardFairy => {
+                material_diff::<Square8, BB8<Square8>, Defs8, 8>(army, opponent)
+            }
+            Variant::Shuuro | Variant::ShuuroFairy => {
+                material_diff::<Square12, BB12<Square12>, Defs12, 12>(army, opponent)
+            }
+        };
+        1.0 / (1.0 + 10f64.powf(-diff as f64 / 400.0))
+    }
+
+    /// Score of `army` in one simulated game; it plays White in even games.
+    fn simulate(&self, army: &Army, opponent: &Army, game: usize) -> f64 {
+        let white_first = game.is_multiple_of(2);
+        let (white, black) = if white_first {
+            (army, opponent)
+        } else {
+            (opponent, army)
+        };
+        let mut prng = Prng::stream(self.seed, game as u64);
+        let (variant, limits) = (self.variant, &self.limits);
+        let result = match variant {
+            Variant::ShuuroMini | Variant::ShuuroMiniFairy => {
+                on_board!(6, simulate_game, variant, white, black, &mut prng, limits)
+            }
+            Variant::Standard | Variant::StandardFairy => {
+                on_board!(8, simulate_game, variant, white, black, &mut prng, limits)
+            }
+            Variant::Shuuro | Variant::ShuuroFairy => {
+                on_board!(12, simulate_game, variant, white, black, &mut prng, limits)
+            }
+        };
+        match (result, white_first) {
+            (GameResult::WhiteWins, true) | (GameResult::BlackWins, false) => 1.0,
+            (GameResult::WhiteWins, false) | (GameResult::BlackWins, true) => 0.0,
+            _ => 0.5,
+        }
+    }
+}
+
+fn material_diff<S, B, D, const FILE: usize>(army: &Army, opponent: &Army) -> i32
+where
+    S: Square,
+    B: BitBoard<S>,
+    D: EngineDefs<S, B, FILE>,
+{
+    let phase = army.phase::<S, B, D, FILE>() + opponent.phase::<S, B, D, FILE>();
+    army.material::<S, B, D, FILE>(phase) - opponent.material::<S, B, D, FILE>(phase)
+}
+
+/// Deploys both armies at random on seeded plinths and plays a short game.
+fn simulate_game<
+    E,
+    S,
+    B,
+    A,
+    P,
+    D,
+    const FILE: usize,
+    const BITBOARD_SIZE: usize,
+    const RANK: usize,
+>(
+    variant: Variant,
+    white: &Army,
+    black: &Army,
+    prng: &mut Prng,
+    limits: &SearchLimits,
+) -> GameResult
+where
+    E: Engine<S, B, A, P, D, FILE, BITBOARD_SIZE, RANK>,
+    S: Square + Hash + Send + 'static,
+    B: BitBoard<S>,
+    A: Attacks<S, B>,
+    P: Sized
+        + Display
+        + Clone
+        + Board<S, B, A>
+        + Sfen<S, B, A>
+        + Placement<S, B, A>
+        + Play<S, B, A>
+        + Rules<S, B, A>
+        + Send
+        + 'static,
+    D: EngineDefs<S, B, FILE>,
+{
+    E::init();
+    let mut engines = [E::new(), E::new()];
+    let mut position = P::new();
+    position.update_variant(variant);
+    for sq in seeded_plinths::<S, B, A>(D::plinth_sections(), position.dimensions(), prng) {
+        position.xor_player_bb(Color::NoColor, sq);
+    }
+    let board = position.generate_sfen();
+    let board = board.split_whitespace().next().unwrap_or_default();
+    let hand = white.to_hand(Color::White) + &black.to_hand(Color::Black);
+    if position.set_sfen(&format!("{board} w {hand} 1")).is_err() {
+        return GameResult::Aborted;
+    }
+
+    while !position.is_hand_empty(position.side_to_move(), PieceType::Plinth) {
+        let moves = engines[0].placement_moves(&position);
+        if moves.is_empty() {
+            return GameResult::Aborted;
+        }
+        if let Move::Put { to, piece } = moves[prng.below(moves.len())] {
+            position.place(piece, to);
+        }
+    }
+
+    let sfen = position.generate_sfen();
+    let _ = position.set_sfen(&sfen);
+    let [white, black] = &mut engines;
+    fight(
+        [white, black],
+        &mut position,
+        limits,
+        SIMULATION_PLIES,
+        &mut vec![],
+    )
+    .unwrap_or_else(|| {
+        let eval = engines[0].static_evaluation(&position);
+        if eval > ADJUDICATION_MARGIN {
+            GameResult::WhiteWins
+        } else if eval < -ADJUDICATION_MARGIN {
+            GameResult::BlackWins
+        } else {
+            GameResult::Draw
+        }
+    })
+}
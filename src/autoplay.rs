@@ -0,0 +1,228 @@
+use shuuro::{
+    Color, Move, PieceType, Selection, Square, Variant,
+    attacks::Attacks,
+    bitboard::BitBoard,
+    position::{Board, Outcome, Placement, Play, Rules, Sfen},
+};
+use std::{fmt::Display, hash::Hash};
+
+use crate::{
+    engine::{Engine, EngineDefs, PLACEMENT_DEPTH, SearchLimits},
+    on_board,
+    prng::Prng,
+    shop::{ShopAdvisor, ShopStrategy},
+};
+
+pub use crate::game_record::{FightMove, GameRecord, GameResult};
+
+/// Fight plies after which the game is adjudicated a draw.
+pub const MAX_FIGHT_PLIES: usize = 400;
+
+// Mate scores are recorded as this many centipawns.
+const MAX_EVAL: i32 = 32000;
+
+const STRATEGIES: [ShopStrategy; 3] = [
+    ShopStrategy::Balanced,
+    ShopStrategy::Aggressive,
+    ShopStrategy::PawnHeavy,
+];
+
+/// Plays a whole game of `variant` against itself. Shop strategies and
+/// plinths are drawn from `seed`, fight moves are searched with `limits`.
+pub fn play_full_game(variant: Variant, seed: u64, limits: SearchLimits) -> GameRecord {
+    match variant {
+        Variant::ShuuroMini | Variant::ShuuroMiniFairy => on_board!(6, play, variant, seed, limits),
+        Variant::Standard | Variant::StandardFairy => on_board!(8, play, variant, seed, limits),
+        Variant::Shuuro | Variant::ShuuroFairy => on_board!(12, play, variant, seed, limits),
+    }
+}
+
+pub fn play<E, S, B, A, P, D, const FILE: usize, const BITBOARD_SIZE: usize, const RANK: usize>(
+    variant: Variant,
+    seed: u64,
+    limits: SearchLimits,
+) -> GameRecord
+where
+    E: Engine<S, B, A, P, D, FILE, BITBOARD_SIZE, RANK>,
+    S: Square + Hash + Send + 'static,
+    B: BitBoard<S>,
+    A: Attacks<S, B>,
+    P: Sized
+        + Display
+        + Clone
+        + Board<S, B, A>
+        + Sfen<S, B, A>
+        + Placement<S, B, A>
+        + Play<S, B, A>
+        + Rules<S, B, A>
+        + Send
+        + 'static,
+    D: EngineDefs<S, B, FILE>,
+{
+    E::init();
+    let mut engines = [E::new(), E::new()];
+    let mut prng = Prng::new(seed);
+
+    let mut selection = Selection::<S>::default();
+    selection.update_variant(variant);
+    let mut shop_moves = vec![];
+    for color in [Color::White, Color::Black] {
+        let strategy = STRATEGIES[prng.below(STRATEGIES.len())];
+        let purchases = ShopAdvisor::new(variant, strategy).buy(&mut selection, color);
+        shop_moves.extend(purchases.iter().map(|mv| mv.to_string()));
+    }
+
+    let mut position = P::new();
+    position.update_variant(variant);
+    for sq in seeded_plinths::<S, B, A>(D::plinth_sections(), position.dimensions(), &mut prng) {
+        position.xor_player_bb(Color::NoColor, sq);
+    }
+    let board = position.generate_sfen();
+    let board = board.split_whitespace().next().unwrap_or_default();
+    let start_sfen = format!("{board} w - 1");
+    let hand = selection.to_sfen(Color::White, true) + &selection.to_sfen(Color::Black, true);
+
+    let mut record = GameRecord {
+        variant,
+        seed,
+        start_sfen,
+        shop_moves,
+        deploy_moves: vec![],
+        fight_sfen: String::new(),
+        fight_moves: vec![],
+        final_sfen: String::new(),
+        result: GameResult::Aborted,
+    };
+    if position.set_sfen(&format!("{board} w {hand} 1")).is_err() {
+        record.final_sfen = position.generate_sfen();
+        return record;
+    }
+
+    while !position.is_hand_empty(position.side_to_move(), PieceType::Plinth) {
+        let limits = SearchLimits {
+            depth: Some(PLACEMENT_DEPTH),
+            ..Default::default()
+        };
+        let engine = &mut engines[position.side_to_move().index()];
+        let Some(Move::Put { to, piece }) = engine.placement(&position, limits).best_move else {
+            record.final_sfen = position.generate_sfen();
+            return record;
+        };
+        position.place(piece, to);
+        record
+            .deploy_moves
+            .push(Move::<S>::Put { to, piece }.to_string());
+    }
+
+    // Reloading the deployed position caches the fight-phase legal moves.
+    let sfen = position.generate_sfen();
+    let _ = position.set_sfen(&sfen);
+    record.fight_sfen = sfen;
+    let [white, black] = &mut engines;
+    record.result = fight(
+        [white, black],
+        &mut position,
+        &limits,
+        MAX_FIGHT_PLIES,
+        &mut record.fight_moves,
+    )
+    .unwrap_or(GameResult::Draw);
+    record.final_sfen = position.generate_sfen();
+    record
+}
+
+/// Plays the fight phase from `position` with White's engine first,
+/// pushing the moves and their evaluations onto `moves`. Returns `None`
+/// when `max_plies` is reached without a result.
+pub(crate) fn fight<
+    E,
+    S,
+    B,
+    A,
+    P,
+    D,
+    const FILE: usize,
+    const BITBOARD_SIZE: usize,
+    const RANK: usize,
+>(
+    engines: [&mut E; 2],
+    position: &mut P,
+    limits: &SearchLimits,
+    max_plies: usize,
+    moves: &mut Vec<FightMove>,
+) -> Option<GameResult>
+where
+    E: Engine<S, B, A, P, D, FILE, BITBOARD_SIZE, RANK>,
+    S: Square + Hash + Send + 'static,
+    B: BitBoard<S>,
+    A: Attacks<S, B>,
+    P: Sized
+        + Display
+        + Clone
+        + Board<S, B, A>
+        + Sfen<S, B, A>
+        + Placement<S, B, A>
+        + Play<S, B, A>
+        + Rules<S, B, A>
+        + Send
+        + 'static,
+    D: EngineDefs<S, B, FILE>,
+{
+    for _ in 0..max_plies {
+        let stm = position.side_to_move();
+        let result = engines[stm.index()].search(position, limits.clone());
+        let Some(best_move) = result.best_move else {
+            if position.in_check(stm) {
+                return Some(GameResult::winner(stm.flip()));
+            }
+            return Some(GameResult::Draw);
+        };
+        moves.push(FightMove {
+            eval: Some(result.score.clamp(-MAX_EVAL, MAX_EVAL)),
+            ..FightMove::new(best_move.to_string())
+        });
+        match position.make_move(best_move) {
+            Ok(Outcome::Checkmate { color }) => return Some(GameResult::winner(color)),
+            Ok(_) => (),
+            // Repetition, stalemate and insufficient material.
+            Err(_) => return Some(GameResult::Draw),
+        }
+    }
+    None
+}
+
+/// Same placement rules as the crate's plinth generator (no two plinths
+/// next to each other within a section), but reproducible from `prng`.
+pub(crate) fn seeded_plinths<S, B, A>(
+    sections: &[(u8, u8, u8, u8, u8)],
+    dimensions: u8,
+    prng: &mut Prng,
+) -> B
+where
+    S: Square,
+    B: BitBoard<S>,
+    A: Attacks<S, B>,
+{
+    let mut plinths = B::empty();
+    for &(rank_from, rank_to, file_from, file_to, count) in sections {
+        let mut section = B::empty();
+        let mut placed = 0;
+        while placed < count {
+            let rank = rank_from + prng.below((rank_to - rank_from) as usize) as u8;
+            let file = file_from + prng.below((file_to - file_from) as usize) as u8;
+            let Some(sq) = S::from_index(rank * dimensions + file) else {
+                continue;
+            };
+            let around = A::get_non_sliding_attacks(PieceType::King, &sq, Color::White, B::empty());
+            if (plinths & &sq).is_empty()
+                && (section & &sq).is_empty()
+                && (section & &around).is_empty()
+            {
+                section |= &sq;
+                placed += 1;
+            }
+        }
+        plinths |= &section;
+    }
+    plinths
+}
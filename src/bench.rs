@@ -0,0 +1,136 @@
+use shuuro::{
+    Square, Variant,
+    attacks::Attacks,
+    bitboard::BitBoard,
+    position::{Board, Placement, Play, Rules, Sfen},
+};
+use std::{
+    fmt::Display,
+    hash::Hash,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    engine::{Engine, EngineDefs, SearchLimits, with_stack},
+    on_board,
+};
+
+// Every bench position is searched to a fixed depth by a fresh engine under
+// the board's fairy variant, so node counts only change when the search or
+// evaluation does.
+// The signature is an FNV-1a hash of each position's node count and best
+// move, which also catches changes that keep the total the same.
+
+pub const DEFAULT_DEPTH: i32 = 4;
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BenchStats {
+    pub positions: usize,
+    pub nodes: u64,
+    pub elapsed: Duration,
+    pub signature: u64,
+}
+
+impl Default for BenchStats {
+    fn default() -> Self {
+        Self {
+            positions: 0,
+            nodes: 0,
+            elapsed: Duration::ZERO,
+            signature: FNV_OFFSET,
+        }
+    }
+}
+
+impl BenchStats {
+    pub fn nps(&self) -> u64 {
+        let micros = self.elapsed.as_micros().max(1);
+        (self.nodes as u128 * 1_000_000 / micros) as u64
+    }
+
+    fn add(&mut self, nodes: u64, best_move: &str, elapsed: Duration) {
+        self.positions += 1;
+        self.nodes += nodes;
+        self.elapsed += elapsed;
+        for byte in nodes.to_le_bytes().iter().chain(best_move.as_bytes()) {
+            self.signature = (self.signature ^ *byte as u64).wrapping_mul(FNV_PRIME);
+        }
+    }
+}
+
+impl Display for BenchStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "positions {} nodes {} time {} nps {} signature {:016x}",
+            self.positions,
+            self.nodes,
+            self.elapsed.as_millis(),
+            self.nps(),
+            self.signature
+        )
+    }
+}
+
+/// Benches the 6x6, 8x8 and 12x12 positions, in that order.
+pub fn run_all(depth: i32) -> BenchStats {
+    with_stack(move || {
+        let mut stats = BenchStats::default();
+        on_board!(6, run, Variant::ShuuroMiniFairy, depth, &mut stats);
+        on_board!(8, run, Variant::StandardFairy, depth, &mut stats);
+        on_board!(12, run, Variant::ShuuroFairy, depth, &mut stats);
+        stats
+    })
+}
+
+/// Searches each of `D::bench_positions` to `depth` with a fresh engine.
+pub fn run<E, S, B, A, P, D, const FILE: usize, const BITBOARD_SIZE: usize, const RANK: usize>(
+    variant: Variant,
+    depth: i32,
+    stats: &mut BenchStats,
+) where
+    E: Engine<S, B, A, P, D, FILE, BITBOARD_SIZE, RANK>,
+    S: Square + Hash + Send + 'static,
+    B: BitBoard<S>,
+    A: Attacks<S, B>,
+    P: Sized
+        + Display
+        + Clone
+        + Board<S, B, A>
+        + Sfen<S, B, A>
+        + Placement<S, B, A>
+        + Play<S, B, A>
+        + Rules<S, B, A>
+        + Send
+        + 'static,
+    D: EngineDefs<S, B, FILE>,
+{
+    E::init();
+    for (index, sfen) in D::bench_positions().iter().enumerate() {
+        let mut engine = E::new();
+        let mut position = P::new();
+        position.update_variant(variant);
+        position
+            .set_sfen(sfen)
+            .expect("bench positions are valid sfens");
+        let limits = SearchLimits {
+            depth: Some(depth),
+            ..Default::default()
+        };
+        let start = Instant::now();
+        let result = engine.search(&position, limits);
+        let elapsed = start.elapsed();
+        let best_move = result
+            .best_move
+            .map_or(String::from("(none)"), |mv| mv.to_string());
+        println!(
+            "info string bench {FILE}x{FILE} position {} nodes {} bestmove {best_move}",
+            index + 1,
+            result.nodes
+        );
+        stats.add(result.nodes, &best_move, elapsed);
+    }
+}
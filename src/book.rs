@@ -0,0 +1,234 @@
+use shuuro::{
+    Color, Move, Square,
+    attacks::Attacks,
+    bitboard::BitBoard,
+    position::{Board, Placement, Play, Rules, Sfen},
+};
+use std::{
+    collections::HashMap,
+    fs,
+    hash::Hash,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use crate::{
+    game_record::{GameRecord, GameResult},
+    prng::Prng,
+    zobrist,
+};
+
+// Binary format, little-endian:
+//
+//   header: magic "SBK1", board width u8, 3 zero bytes
+//   entry:  key u64, from u8, to u8, weight u16, learn u32
+//
+// Entries are sorted by key, the moves of one position by weight, best
+// first. Keys come from `zobrist::hash` and squares are the crate's square
+// indices, so the same format serves 6x6, 8x8 and 12x12 boards; the width
+// in the header keeps books from being used on the wrong board.
+//
+// `weight` is what the move scored in the source games (2 per win, 1 per
+// draw) and `learn` how many games played it.
+
+/// Moves per game `makebook` counts by default.
+pub const DEFAULT_PLIES: usize = 16;
+
+const MAGIC: &[u8; 4] = b"SBK1";
+const HEADER_SIZE: usize = 8;
+const ENTRY_SIZE: usize = 16;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BookEntry {
+    pub key: u64,
+    pub from: u8,
+    pub to: u8,
+    pub weight: u16,
+    pub learn: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Book {
+    files: u8,
+    entries: Vec<BookEntry>,
+}
+
+impl Book {
+    pub fn new(files: u8, mut entries: Vec<BookEntry>) -> Self {
+        entries.sort_by_key(|e| (e.key, u16::MAX - e.weight, e.from, e.to));
+        Self { files, entries }
+    }
+
+    /// Board width the book was made for.
+    pub fn files(&self) -> u8 {
+        self.files
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Book moves of the position with `key`, best first.
+    pub fn entries(&self, key: u64) -> &[BookEntry] {
+        let start = self.entries.partition_point(|e| e.key < key);
+        let end = self.entries.partition_point(|e| e.key <= key);
+        &self.entries[start..end]
+    }
+
+    /// Random move of the position with `key`, chosen by weight.
+    pub fn pick(&self, key: u64, prng: &mut Prng) -> Option<&BookEntry> {
+        let entries = self.entries(key);
+        let total: u64 = entries.iter().map(|e| e.weight as u64).sum();
+        if total == 0 {
+            return None;
+        }
+        let mut roll = prng.next_u64() % total;
+        entries.iter().find(|e| {
+            if roll < e.weight as u64 {
+                return true;
+            }
+            roll -= e.weight as u64;
+            false
+        })
+    }
+
+    pub fn load<T: AsRef<Path>>(path: T) -> io::Result<Self> {
+        let data = fs::read(path)?;
+        if data.len() < HEADER_SIZE
+            || &data[..4] != MAGIC
+            || !(data.len() - HEADER_SIZE).is_multiple_of(ENTRY_SIZE)
+        {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "bad book file"));
+        }
+        let entries = data[HEADER_SIZE..]
+            .chunks_exact(ENTRY_SIZE)
+            .map(|e| BookEntry {
+                key: u64::from_le_bytes(e[..8].try_into().unwrap()),
+                from: e[8],
+                to: e[9],
+                weight: u16::from_le_bytes([e[10], e[11]]),
+                learn: u32::from_le_bytes(e[12..].try_into().unwrap()),
+            })
+            .collect();
+        Ok(Self::new(data[4], entries))
+    }
+
+    pub fn save<T: AsRef<Path>>(&self, path: T) -> io::Result<()> {
+        let mut out = BufWriter::new(fs::File::create(path)?);
+        out.write_all(MAGIC)?;
+        out.write_all(&[self.files, 0, 0, 0])?;
+        for e in &self.entries {
+            out.write_all(&e.key.to_le_bytes())?;
+            out.write_all(&[e.from, e.to])?;
+            out.write_all(&e.weight.to_le_bytes())?;
+            out.write_all(&e.learn.to_le_bytes())?;
+        }
+        out.flush()
+    }
+}
+
+/// `OwnBook` and `BookFile` settings of an engine.
+#[derive(Clone, Debug)]
+pub struct BookState {
+    pub enabled: bool,
+    pub book: Option<Book>,
+    prng: Prng,
+}
+
+impl Default for BookState {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            book: None,
+            prng: Prng::new(0),
+        }
+    }
+}
+
+impl BookState {
+    /// `(from, to)` square indices of a book move, when the book is in use.
+    pub fn pick(&mut self, key: u64) -> Option<(u8, u8)> {
+        if !self.enabled {
+            return None;
+        }
+        let entry = self.book.as_ref()?.pick(key, &mut self.prng)?;
+        Some((entry.from, entry.to))
+    }
+}
+
+/// Builds a book from the fight moves of `games`. The first `plies` moves
+/// of every game are counted, moves seen in fewer than `min_games` games
+/// are left out. Games that don't fit the board or contain an illegal move
+/// are skipped from that point on.
+pub fn make<S, B, A, P>(games: &[GameRecord], plies: usize, min_games: u32) -> Book
+where
+    S: Square + Hash,
+    B: BitBoard<S>,
+    A: Attacks<S, B>,
+    P: Board<S, B, A> + Sfen<S, B, A> + Placement<S, B, A> + Play<S, B, A> + Rules<S, B, A>,
+{
+    let files = P::new().dimensions();
+    let mut stats: HashMap<(u64, u8, u8), (u32, u32)> = HashMap::new();
+    for game in games {
+        let sfen = if game.fight_sfen.is_empty() {
+            // Only the drops were recorded, the replay finds the fight sfen.
+            let mut game = game.clone();
+            if game.replay::<S, B, A, P>().is_err() {
+                continue;
+            }
+            game.fight_sfen
+        } else {
+            game.fight_sfen.clone()
+        };
+        let result = game.result;
+        let mut position = P::new();
+        if position.set_sfen(&sfen).is_err() {
+            continue;
+        }
+        for mv in game.fight_moves.iter().take(plies) {
+            let Some((from, to)) = Move::<S>::from_sfen(&mv.mv).and_then(|mv| mv.info()) else {
+                break;
+            };
+            let stm = position.side_to_move();
+            let legal = position
+                .legal_moves(stm)
+                .get(&from)
+                .is_some_and(|targets| (*targets & &to).is_any());
+            if !legal {
+                break;
+            }
+            let score = match (result, stm) {
+                (GameResult::WhiteWins, Color::White) | (GameResult::BlackWins, Color::Black) => 2,
+                (GameResult::Draw, _) => 1,
+                _ => 0,
+            };
+            let key = (
+                zobrist::hash(&position),
+                from.index() as u8,
+                to.index() as u8,
+            );
+            let entry = stats.entry(key).or_default();
+            entry.0 += score;
+            entry.1 += 1;
+            if position.make_move(Move::new(from, to)).is_err() {
+                break;
+            }
+        }
+    }
+    let entries = stats
+        .into_iter()
+        .filter(|(_, (_, learn))| *learn >= min_games)
+        .map(|((key, from, to), (weight, learn))| BookEntry {
+            key,
+            from,
+            to,
+            weight: weight.min(u16::MAX as u32) as u16,
+            learn,
+        })
+        .collect();
+    Book::new(files, entries)
+}
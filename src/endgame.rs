@@ -0,0 +1,106 @@
+use shuuro::{Color, PieceType, Square};
+
+/// Scale factor that leaves the evaluation unchanged.
+pub const SCALE_NORMAL: i32 = 64;
+
+/// Base score of a won basic mate, before driving the king to the corner.
+pub const KNOWN_WIN: i32 = 10_000;
+
+/// Pieces that mate a bare king with their own king's help.
+const MATING_PIECES: [PieceType; 4] = [
+    PieceType::Queen,
+    PieceType::Rook,
+    PieceType::Chancellor,
+    PieceType::ArchBishop,
+];
+
+/// Pieces that can't mate on their own.
+const MINOR_PIECES: [PieceType; 3] = [PieceType::Bishop, PieceType::Knight, PieceType::Giraffe];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Endgame {
+    /// Neither side can mate.
+    Draw,
+    /// The given side mates a bare king.
+    Mate(Color),
+}
+
+/// Known endgame for `[white, black]` piece counts from `count_material`.
+pub fn classify(piece_counts: &[[u32; 9]; 2]) -> Option<Endgame> {
+    if insufficient_material(&piece_counts[0]) && insufficient_material(&piece_counts[1]) {
+        return Some(Endgame::Draw);
+    }
+    for (strong, weak, color) in [
+        (&piece_counts[0], &piece_counts[1], Color::White),
+        (&piece_counts[1], &piece_counts[0], Color::Black),
+    ] {
+        let mating = MATING_PIECES.iter().any(|pt| strong[pt.index()] > 0);
+        if mating && non_king(weak) == 0 {
+            return Some(Endgame::Mate(color));
+        }
+    }
+    None
+}
+
+/// At most one minor piece (Bishop, Knight or Giraffe) and nothing else.
+pub fn insufficient_material(counts: &[u32; 9]) -> bool {
+    let minors: u32 = MINOR_PIECES.iter().map(|pt| counts[pt.index()]).sum();
+    minors <= 1 && non_king(counts) == minors
+}
+
+fn non_king(counts: &[u32; 9]) -> u32 {
+    counts.iter().sum::<u32>() - counts[PieceType::King.index()]
+}
+
+/// Bonus for the side mating a bare king: the weak king near a corner and
+/// the kings close together. `size` is the board width.
+pub fn drive_to_corner<S: Square>(strong_king: S, weak_king: S, size: u8) -> i32 {
+    let edge = |v: u8| v.min(size - 1 - v) as i32;
+    let corner = edge(weak_king.file()) + edge(weak_king.rank());
+    let distance = strong_king
+        .file()
+        .abs_diff(weak_king.file())
+        .max(strong_king.rank().abs_diff(weak_king.rank())) as i32;
+    20 * (size as i32 - corner) + 10 * (size as i32 - distance)
+}
+
+/// How much of the evaluation `strong`, the side ahead, can expect to
+/// convert, out of `SCALE_NORMAL`. `value` gives the midgame piece value.
+pub fn scale_factor(
+    piece_counts: &[[u32; 9]; 2],
+    strong: Color,
+    opposite_bishops: bool,
+    value: impl Fn(PieceType) -> i32,
+) -> i32 {
+    let (us, them) = match strong {
+        Color::Black => (&piece_counts[1], &piece_counts[0]),
+        _ => (&piece_counts[0], &piece_counts[1]),
+    };
+    let pawn = PieceType::Pawn.index();
+    let pieces = |counts: &[u32; 9]| -> i32 {
+        MATING_PIECES
+            .iter()
+            .chain(&MINOR_PIECES)
+            .map(|pt| value(*pt) * counts[pt.index()] as i32)
+            .sum()
+    };
+
+    if us[pawn] == 0 {
+        // Two knights can't force mate.
+        if non_king(us) == 2 && us[PieceType::Knight.index()] == 2 {
+            return 2;
+        }
+        // Up no more than a minor piece without pawns, e.g. KR vs KB.
+        if pieces(us) - pieces(them) <= value(PieceType::Bishop) {
+            return 8;
+        }
+    }
+
+    let only_bishop = |counts: &[u32; 9]| {
+        counts[PieceType::Bishop.index()] == 1 && non_king(counts) == 1 + counts[pawn]
+    };
+    if opposite_bishops && only_bishop(us) && only_bishop(them) {
+        return 32;
+    }
+    SCALE_NORMAL
+}
@@ -1,11 +1,135 @@
 use shuuro::{
-    Color, Move, PieceType, Square,
+    Color, Move, MoveData, Piece, PieceType, Square,
     attacks::Attacks,
     bitboard::BitBoard,
     piece_type::PieceTypeIter,
     position::{Board, Placement, Play, Rules, Sfen},
 };
-use std::{cmp, collections::HashMap, f32::INFINITY, fmt::Display, hash::Hash};
+use std::{
+    cmp,
+    fmt::Display,
+    hash::Hash,
+    io::{self, Write},
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    annotate::{self, AnnotateOptions},
+    army::{Army, ArmyEvaluator},
+    bench,
+    book::{self, Book, BookState},
+    endgame::{self, Endgame, KNOWN_WIN, SCALE_NORMAL},
+    game_record::{GameRecord, GameResult, RecordError},
+    gensfens::{self, GensfensOptions, Record},
+    imbalance::{ImbalanceTable, Sample},
+    match_runner::{self, MatchOptions},
+    move_list::{self, MoveList},
+    nnue::{Network, Nnue},
+    pawn_table::{PawnEntry, PawnTable},
+    personality::{self, EvalGroup, Personality},
+    shop::ShopAdvisor,
+    skill::{self, SkillCaps, SkillState},
+    testsuite::{self, EpdEntry},
+    zobrist,
+};
+
+pub const DEFAULT_DEPTH: i32 = 3;
+pub const MAX_DEPTH: i32 = 64;
+pub const PLACEMENT_DEPTH: i32 = 2;
+/// Stack of every thread that searches. Positions are large and searched
+/// recursively by value.
+pub const STACK_SIZE: usize = 256 * 1024 * 1024;
+
+// Pieces that can stand on plinths.
+pub(crate) const JUMPERS: [PieceType; 4] = [
+    PieceType::Knight,
+    PieceType::Chancellor,
+    PieceType::ArchBishop,
+    PieceType::Giraffe,
+];
+
+// King danger by attack units, grows roughly quadratically before it levels off.
+const KING_DANGER: [i32; 64] = [
+    0, 0, 1, 2, 3, 5, 7, 9, 12, 15, 18, 22, 26, 30, 35, 39, 44, 50, 56, 62, 68, 75, 82, 85, 89, 97,
+    105, 113, 122, 131, 140, 150, 169, 180, 191, 202, 213, 225, 237, 248, 260, 272, 283, 295, 307,
+    319, 330, 342, 354, 366, 377, 389, 401, 412, 424, 436, 448, 459, 471, 483, 494, 500, 500, 500,
+];
+
+/// Runs `f` on a thread with `STACK_SIZE` of stack and returns its result.
+pub fn with_stack<T: Send + 'static>(f: impl FnOnce() -> T + Send + 'static) -> T {
+    thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(f)
+        .expect("failed to spawn search thread")
+        .join()
+        .expect("search thread panicked")
+}
+
+/// Calls the generic function `f` with the engine, square, bitboard, attacks,
+/// position and defs types and the sizes of the 6, 8 or 12 file board:
+/// `on_board!(8, bench::run, Variant::StandardFairy, depth, &mut stats)`.
+#[macro_export]
+macro_rules! on_board {
+    (6, $($f:ident)::+ $(, $arg:expr)* $(,)?) => {
+        $($f)::+::<
+            $crate::engine6::search::Engine6,
+            ::shuuro::shuuro6::square6::Square6,
+            ::shuuro::shuuro6::bitboard6::BB6<::shuuro::shuuro6::square6::Square6>,
+            ::shuuro::shuuro6::attacks6::Attacks6<
+                ::shuuro::shuuro6::square6::Square6,
+                ::shuuro::shuuro6::bitboard6::BB6<::shuuro::shuuro6::square6::Square6>,
+            >,
+            ::shuuro::shuuro6::position6::P6<
+                ::shuuro::shuuro6::square6::Square6,
+                ::shuuro::shuuro6::bitboard6::BB6<::shuuro::shuuro6::square6::Square6>,
+            >,
+            $crate::engine6::search::Defs6,
+            6,
+            36,
+            4,
+        >($($arg),*)
+    };
+    (8, $($f:ident)::+ $(, $arg:expr)* $(,)?) => {
+        $($f)::+::<
+            $crate::engine8::search::Engine8,
+            ::shuuro::shuuro8::square8::Square8,
+            ::shuuro::shuuro8::bitboard8::BB8<::shuuro::shuuro8::square8::Square8>,
+            ::shuuro::shuuro8::attacks8::Attacks8<
+                ::shuuro::shuuro8::square8::Square8,
+                ::shuuro::shuuro8::bitboard8::BB8<::shuuro::shuuro8::square8::Square8>,
+            >,
+            ::shuuro::shuuro8::position8::P8<
+                ::shuuro::shuuro8::square8::Square8,
+                ::shuuro::shuuro8::bitboard8::BB8<::shuuro::shuuro8::square8::Square8>,
+            >,
+            $crate::engine8::search::Defs8,
+            8,
+            64,
+            7,
+        >($($arg),*)
+    };
+    (12, $($f:ident)::+ $(, $arg:expr)* $(,)?) => {
+        $($f)::+::<
+            $crate::engine12::search::Engine12,
+            ::shuuro::shuuro12::square12::Square12,
+            ::shuuro::shuuro12::bitboard12::BB12<::shuuro::shuuro12::square12::Square12>,
+            ::shuuro::shuuro12::attacks12::Attacks12<
+                ::shuuro::shuuro12::square12::Square12,
+                ::shuuro::shuuro12::bitboard12::BB12<::shuuro::shuuro12::square12::Square12>,
+            >,
+            ::shuuro::shuuro12::position12::P12<
+                ::shuuro::shuuro12::square12::Square12,
+                ::shuuro::shuuro12::bitboard12::BB12<::shuuro::shuuro12::square12::Square12>,
+            >,
+            $crate::engine12::search::Defs12,
+            12,
+            144,
+            11,
+        >($($arg),*)
+    };
+}
 
 // Game phase
 #[derive(Debug)]
@@ -14,6 +138,52 @@ pub enum GamePhase {
     Endgame,
 }
 
+/// Plinth evaluation terms, weighted per variant by `EngineDefs::plinth_weight`.
+#[derive(Clone, Copy, Debug)]
+pub enum PlinthTerm {
+    /// Free or enemy-held plinth attacked by a jumper.
+    Control,
+    /// Jumper standing on a plinth.
+    Outpost,
+    /// Slider ray ending on a plinth.
+    BlockedSlider,
+    /// Plinth directly in front of the king.
+    KingShelter,
+    /// Pawn with a plinth somewhere ahead on its file.
+    BlockedPawn,
+}
+
+/// Fairy piece evaluation terms, weighted per variant by
+/// `EngineDefs::fairy_weight`.
+#[derive(Clone, Copy, Debug)]
+pub enum FairyTerm {
+    /// Chancellor on a file without pawns.
+    ChancellorOpenFile,
+    /// Chancellor on a file without own pawns.
+    ChancellorHalfOpenFile,
+    /// Archbishop diagonal square in the enemy half.
+    ArchbishopDiagonal,
+    /// Giraffe attacking two kings or pieces worth more than itself.
+    GiraffeFork,
+    /// Giraffe without a safe square.
+    GiraffeTrapped,
+    /// Giraffe with a single safe square.
+    GiraffeCornered,
+    /// Archbishop against a side without bishops.
+    ArchbishopNoBishops,
+    /// Two or more Chancellors and Archbishops.
+    HybridPair,
+}
+
+/// Result of playing a move during search.
+pub enum Child<P> {
+    Position(P),
+    /// Repetition or insufficient material.
+    Draw,
+    /// A move that leaves the mover's king attacked.
+    Illegal,
+}
+
 impl GamePhase {
     pub fn from_game_state(game_phase_value: i32) -> Self {
         if game_phase_value < 24 {
@@ -24,10 +194,106 @@ impl GamePhase {
     }
 }
 
+#[derive(Clone, Debug, Default)]
+pub struct SearchLimits {
+    pub depth: Option<i32>,
+    pub nodes: Option<u64>,
+    pub movetime: Option<Duration>,
+}
+
+impl SearchLimits {
+    /// Parses `depth <n> nodes <n> movetime <ms>`, unknown tokens are skipped.
+    pub fn parse(args: &str) -> Self {
+        let mut limits = Self::default();
+        let mut tokens = args.split_whitespace();
+        while let Some(token) = tokens.next() {
+            match token {
+                "depth" => limits.depth = tokens.next().and_then(|v| v.parse().ok()),
+                "nodes" => limits.nodes = tokens.next().and_then(|v| v.parse().ok()),
+                "movetime" => {
+                    limits.movetime = tokens
+                        .next()
+                        .and_then(|v| v.parse().ok())
+                        .map(Duration::from_millis)
+                }
+                _ => (),
+            }
+        }
+        limits
+    }
+
+    pub(crate) fn max_depth(&self) -> i32 {
+        match (self.depth, self.nodes, self.movetime) {
+            (Some(depth), _, _) => depth.clamp(1, MAX_DEPTH),
+            (None, None, None) => DEFAULT_DEPTH,
+            _ => MAX_DEPTH,
+        }
+    }
+}
+
+/// Bookkeeping of the running search.
+#[derive(Clone, Debug)]
+pub struct SearchState {
+    pub nodes: u64,
+    pub stopped: bool,
+    limits: SearchLimits,
+    start: Instant,
+}
+
+impl Default for SearchState {
+    fn default() -> Self {
+        Self::new(SearchLimits::default())
+    }
+}
+
+impl SearchState {
+    pub fn new(limits: SearchLimits) -> Self {
+        Self {
+            nodes: 0,
+            stopped: false,
+            limits,
+            start: Instant::now(),
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    /// Counts a node and checks the node and time limits.
+    fn visit(&mut self) -> bool {
+        self.nodes += 1;
+        if self.limits.nodes.is_some_and(|n| self.nodes > n) {
+            self.stopped = true;
+        }
+        if self.nodes.is_multiple_of(1024)
+            && self
+                .limits
+                .movetime
+                .is_some_and(|t| self.start.elapsed() >= t)
+        {
+            self.stopped = true;
+        }
+        self.stopped
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SearchResult<S: Square> {
+    pub best_move: Option<Move<S>>,
+    /// Score from White's point of view.
+    pub score: i32,
+    pub depth: i32,
+    pub nodes: u64,
+}
+
 pub trait EngineDefs<S: Square, B: BitBoard<S>, const FILE: usize> {
-    fn get_piece_value(piece_type: PieceType, color: Color) -> i32;
+    fn get_piece_value(piece_type: PieceType) -> i32;
 
-    fn get_endgame_piece_value(piece_type: PieceType, color: Color) -> i32;
+    fn get_endgame_piece_value(piece_type: PieceType) -> i32;
+
+    /// Score added for White, the only intended colour asymmetry.
+    fn side_bias() -> i32;
 
     fn get_pst_value(square: S, piece_type: PieceType, color: Color) -> i32;
 
@@ -42,10 +308,40 @@ pub trait EngineDefs<S: Square, B: BitBoard<S>, const FILE: usize> {
     fn get_player_side(color: Color) -> B;
 
     fn phase_weight(piece_type: usize) -> i32;
+
+    /// Phase above which midgame values apply, and the cap on the phase.
+    fn midgame_min() -> (i32, i32);
+
     fn all_files() -> [B; FILE];
+
+    fn mobility_weight(piece_type: PieceType, endgame: bool) -> i32;
+
+    fn plinth_weight(term: PlinthTerm) -> i32;
+
+    fn fairy_weight(term: FairyTerm) -> i32;
+
+    /// Default imbalance coefficients, `ImbalanceFile` overrides them.
+    fn imbalance_table() -> &'static ImbalanceTable;
+
+    /// Squares in front of a pawn on its own and neighbouring files.
+    fn passed_pawn_mask(square: S, color: Color) -> B;
+
+    /// Fight-phase positions used to seed self-play.
+    fn start_positions() -> &'static [&'static str];
+
+    /// Fight-phase positions searched by `bench`.
+    fn bench_positions() -> &'static [&'static str];
+
+    /// Search caps of the skill levels.
+    fn skill_caps() -> &'static SkillCaps;
+
+    /// Areas where plinths are generated: (rank from, rank to, file from,
+    /// file to, count).
+    fn plinth_sections() -> &'static [(u8, u8, u8, u8, u8)];
 }
 
-pub trait Engine<S, B, A, P, D, const FILE: usize, const BITBOARD_SIZE: usize, const RANK: usize>
+pub trait Engine<S, B, A, P, D, const FILE: usize, const BITBOARD_SIZE: usize, const RANK: usize>:
+    Sized + Send
 where
     S: Square + Hash + Send + 'static,
     B: BitBoard<S>,
@@ -70,6 +366,7 @@ where
 
         let mut position = P::new();
         position.set_sfen(sfen).unwrap();
+        let mut game = GameRecord::new(position.variant(), sfen);
 
         loop {
             println!("{}", position);
@@ -78,24 +375,326 @@ where
             let input = input.trim();
 
             match input {
+                "uci" => {
+                    println!("id name shuuro-engine");
+                    println!("option name EvalFile type string default <empty>");
+                    println!("option name ImbalanceFile type string default <empty>");
+                    println!("option name TablebasePath type string default <empty>");
+                    println!("option name OwnBook type check default false");
+                    println!("option name BookFile type string default <empty>");
+                    println!(
+                        "option name Skill Level type spin default {0} min 0 max {0}",
+                        skill::MAX_LEVEL
+                    );
+                    println!("option name UCI_LimitStrength type check default false");
+                    println!(
+                        "option name UCI_Elo type spin default {1} min {0} max {1}",
+                        skill::MIN_ELO,
+                        skill::MAX_ELO
+                    );
+                    println!("{}", personality::uci_option());
+                    println!("uciok");
+                }
                 "isready" => println!("readyok"),
                 "quit" => break,
+                cmd if cmd.starts_with("setoption") => {
+                    let (name, value) = parse_setoption(cmd);
+                    self.set_option(&name, &value);
+                }
                 cmd if cmd.starts_with("position") => {
-                    // Parse position command
-                    println!("{position}");
+                    let Some((sfen, moves)) = parse_position(cmd) else {
+                        continue;
+                    };
+                    let mut next = P::new();
+                    if next.set_sfen(&sfen).is_err() {
+                        println!("info string invalid sfen");
+                        continue;
+                    }
+                    let mut next_game = GameRecord::new(next.variant(), &sfen);
+                    for mv in moves {
+                        let before = next.generate_sfen();
+                        let applied = match Move::<S>::from_sfen(&mv) {
+                            Some(Move::Put { to, piece }) => next.place(piece, to).is_some(),
+                            Some(mv) => next.make_move(mv).is_ok(),
+                            None => false,
+                        };
+                        if !applied {
+                            println!("info string illegal move {mv}");
+                            break;
+                        }
+                        next_game.push(mv, &before);
+                    }
+                    position = next;
+                    game = next_game;
+                }
+                cmd if cmd.starts_with("shop") => {
+                    let strategy = cmd
+                        .split_whitespace()
+                        .nth(1)
+                        .and_then(|s| s.parse().ok())
+                        .unwrap_or_default();
+                    let advisor = ShopAdvisor::new(position.variant(), strategy);
+                    let purchases: Vec<String> = advisor
+                        .purchases::<S>(position.side_to_move())
+                        .iter()
+                        .map(|mv| mv.to_string())
+                        .collect();
+                    println!("shop {}", purchases.join(" "));
+                }
+                cmd if cmd.starts_with("army") => {
+                    // army <army> <opponent> [games <n>] [seed <n>] [search limits]
+                    let mut tokens = cmd.split_whitespace().skip(1);
+                    let armies = (
+                        tokens.next().and_then(|s| s.parse::<Army>().ok()),
+                        tokens.next().and_then(|s| s.parse::<Army>().ok()),
+                    );
+                    let (Some(army), Some(opponent)) = armies else {
+                        println!(
+                            "info string usage: army <army> <opponent> [games <n>] [seed <n>]"
+                        );
+                        continue;
+                    };
+                    let rest: Vec<&str> = tokens.collect();
+                    let value = |name: &str| {
+                        rest.iter()
+                            .position(|token| *token == name)
+                            .and_then(|index| rest.get(index + 1))
+                            .and_then(|value| value.parse().ok())
+                            .unwrap_or(0)
+                    };
+                    let (games, seed) = (value("games") as usize, value("seed"));
+                    let limits = SearchLimits::parse(&rest.join(" "));
+                    let score = ArmyEvaluator::new(position.variant())
+                        .with_simulation(games, seed, limits)
+                        .expected_score(&army, &opponent);
+                    println!("army score {score:.3}");
+                }
+                cmd if cmd.starts_with("makebook") => {
+                    // makebook <games> <book> [plies <n>] [min <n>]
+                    let tokens: Vec<&str> = cmd.split_whitespace().skip(1).collect();
+                    let (Some(games), Some(output)) = (tokens.first(), tokens.get(1)) else {
+                        println!(
+                            "info string usage: makebook <games> <book> [plies <n>] [min <n>]"
+                        );
+                        continue;
+                    };
+                    let value = |name: &str, default: usize| {
+                        tokens
+                            .iter()
+                            .position(|token| *token == name)
+                            .and_then(|index| tokens.get(index + 1))
+                            .and_then(|value| value.parse().ok())
+                            .unwrap_or(default)
+                    };
+                    let games = std::fs::read_to_string(games).and_then(|text| {
+                        GameRecord::parse_all(&text).map_err(|err| {
+                            io::Error::new(io::ErrorKind::InvalidData, err.to_string())
+                        })
+                    });
+                    let made = games.and_then(|games| {
+                        let book = book::make::<S, B, A, P>(
+                            &games,
+                            value("plies", book::DEFAULT_PLIES),
+                            value("min", 1) as u32,
+                        );
+                        book.save(output).map(|_| book.len())
+                    });
+                    match made {
+                        Ok(entries) => println!("info string makebook wrote {entries} entries"),
+                        Err(err) => println!("info string makebook failed: {err}"),
+                    }
+                }
+                cmd if cmd.starts_with("tuneimbalance") => {
+                    // tuneimbalance <gensfens text file> <output> [iterations <n>]
+                    let tokens: Vec<&str> = cmd.split_whitespace().skip(1).collect();
+                    let (Some(input), Some(output)) = (tokens.first(), tokens.get(1)) else {
+                        println!(
+                            "info string usage: tuneimbalance <file> <output> [iterations <n>]"
+                        );
+                        continue;
+                    };
+                    let iterations = match tokens.get(2..4) {
+                        Some(["iterations", n]) => n.parse().unwrap_or(1000),
+                        _ => 1000,
+                    };
+                    let records: Vec<Record> = match std::fs::read_to_string(input) {
+                        Ok(text) => text.lines().filter_map(|l| l.parse().ok()).collect(),
+                        Err(err) => {
+                            println!("info string tuneimbalance failed: {err}");
+                            continue;
+                        }
+                    };
+                    let samples = self.imbalance_samples(&position, &records);
+                    let table = *self.get_imbalance_table();
+                    let scale = table.fit_scale(&samples);
+                    let tuned = table.tune(&samples, scale, iterations);
+                    println!(
+                        "info string tuneimbalance {} samples scale {scale:.6} error {:.6} -> {:.6}",
+                        samples.len(),
+                        table.error(&samples, scale),
+                        tuned.error(&samples, scale)
+                    );
+                    if let Err(err) = std::fs::write(output, tuned.to_string()) {
+                        println!("info string tuneimbalance failed: {err}");
+                    }
+                }
+                cmd if cmd.starts_with("tbgen") => {
+                    // tbgen <signature> <dir>, with the plinths of the current position
+                    let mut tokens = cmd.split_whitespace().skip(1);
+                    let (Some(signature), Some(dir)) = (tokens.next(), tokens.next()) else {
+                        println!("info string usage: tbgen <signature> <dir>");
+                        continue;
+                    };
+                    match self.generate_tablebase(&position, signature, dir) {
+                        Ok(written) => println!("info string tbgen wrote {written} tables"),
+                        Err(err) => println!("info string tbgen failed: {err}"),
+                    }
+                }
+                cmd if cmd.starts_with("match") => {
+                    let options = MatchOptions::parse(cmd.trim_start_matches("match"));
+                    match match_runner::run::<Self, S, B, A, P, D, FILE, BITBOARD_SIZE, RANK>(
+                        &options,
+                    ) {
+                        Ok((stats, verdict)) => {
+                            let (lower, upper) = options.sprt.bounds();
+                            println!(
+                                "match {stats} llr {:.2} ({lower:.2}, {upper:.2}) {verdict}",
+                                stats.llr(&options.sprt)
+                            );
+                        }
+                        Err(err) => println!("info string match failed: {err}"),
+                    }
+                }
+                cmd if cmd.starts_with("annotate") => {
+                    let options = AnnotateOptions::parse(cmd.trim_start_matches("annotate"));
+                    let network = self.get_nnue().map(|nnue| nnue.network().clone());
+                    match annotate::run::<Self, S, B, A, P, D, FILE, BITBOARD_SIZE, RANK>(
+                        &options, network,
+                    ) {
+                        Ok(stats) => println!("info string annotate wrote {stats}"),
+                        Err(err) => println!("info string annotate failed: {err}"),
+                    }
+                }
+                cmd if cmd.starts_with("bench") => {
+                    // bench [depth]
+                    let depth = cmd
+                        .split_whitespace()
+                        .nth(1)
+                        .and_then(|depth| depth.parse().ok())
+                        .unwrap_or(bench::DEFAULT_DEPTH);
+                    println!("bench {}", bench::run_all(depth));
+                }
+                cmd if cmd.starts_with("testsuite") => {
+                    // testsuite <file> <milliseconds per position>
+                    let mut tokens = cmd.split_whitespace().skip(1);
+                    let (Some(file), Some(Ok(time))) =
+                        (tokens.next(), tokens.next().map(str::parse::<u64>))
+                    else {
+                        println!("info string usage: testsuite <file> <time>");
+                        continue;
+                    };
+                    let entries = match EpdEntry::load(file) {
+                        Ok(entries) => entries,
+                        Err(err) => {
+                            println!("info string testsuite failed: {err}");
+                            continue;
+                        }
+                    };
+                    let limits = SearchLimits {
+                        movetime: Some(Duration::from_millis(time)),
+                        ..Default::default()
+                    };
+                    let results = testsuite::run(self, &entries, &limits);
+                    let mut total = Duration::ZERO;
+                    for result in &results {
+                        let best_move = result.best_move.as_deref().unwrap_or("(none)");
+                        match result.solved_in {
+                            Some(time) => {
+                                total += time;
+                                println!(
+                                    "info string {} solved in {:.3}s with {best_move} depth {}",
+                                    result.id,
+                                    time.as_secs_f64(),
+                                    result.depth
+                                );
+                            }
+                            None => println!(
+                                "info string {} failed with {best_move} depth {}",
+                                result.id, result.depth
+                            ),
+                        }
+                    }
+                    let solved = results.iter().filter(|r| r.solved_in.is_some()).count();
+                    println!(
+                        "testsuite solved {solved}/{} in {:.3}s",
+                        results.len(),
+                        total.as_secs_f64()
+                    );
+                }
+                cmd if cmd.starts_with("perft") => {
+                    // perft <depth>
+                    let Some(depth) = cmd
+                        .split_whitespace()
+                        .nth(1)
+                        .and_then(|depth| depth.parse::<u32>().ok())
+                        .filter(|depth| *depth > 0)
+                    else {
+                        println!("info string usage: perft <depth>");
+                        continue;
+                    };
+                    let start = Instant::now();
+                    let mut nodes = 0;
+                    for mv in MoveList::pseudo_legal(&position, position.side_to_move()) {
+                        let count = match self.child_position(&position, &mv) {
+                            Child::Position(child) => self.perft(&child, depth - 1),
+                            Child::Draw => u64::from(depth == 1),
+                            Child::Illegal => continue,
+                        };
+                        println!("{mv}: {count}");
+                        nodes += count;
+                    }
+                    let elapsed = start.elapsed().as_millis();
+                    println!("perft {depth} nodes {nodes} time {elapsed}");
+                }
+                cmd if cmd.starts_with("gensfens") => {
+                    let options = GensfensOptions::parse(cmd.trim_start_matches("gensfens"));
+                    let network = self.get_nnue().map(|nnue| nnue.network().clone());
+                    match gensfens::run::<Self, S, B, A, P, D, FILE, BITBOARD_SIZE, RANK>(
+                        &options, network,
+                    ) {
+                        Ok(written) => println!("info string gensfens wrote {written} positions"),
+                        Err(err) => println!("info string gensfens failed: {err}"),
+                    }
                 }
                 cmd if cmd.starts_with("go") => {
                     // Start search and return best move
-
-                    let best_move = self.alpha_beta_search(
-                        &position,
-                        0,
-                        -INFINITY as i32,
-                        INFINITY as i32,
-                        position.side_to_move(),
+                    let limits = SearchLimits::parse(cmd.trim_start_matches("go"));
+                    if !position.is_hand_empty(position.side_to_move(), PieceType::Plinth) {
+                        let result = self.placement(&position, limits);
+                        println!(
+                            "info depth {} score cp {} nodes {}",
+                            result.depth, result.score, result.nodes
+                        );
+                        match result.best_move {
+                            Some(best_move) => println!("bestmove {best_move}"),
+                            None => println!("bestmove (none)"),
+                        }
+                        continue;
+                    }
+                    if let Some(book_move) = self.book_move(&position) {
+                        println!("info string book move");
+                        println!("bestmove {book_move}");
+                        continue;
+                    }
+                    let result = self.search(&position, limits);
+                    println!(
+                        "info depth {} score cp {} nodes {}",
+                        result.depth, result.score, result.nodes
                     );
-                    println!("bestmove {:?}", self.get_best_move());
-                    println!("bestmove {:?}", best_move);
+                    match result.best_move {
+                        Some(best_move) => println!("bestmove {best_move}"),
+                        None => println!("bestmove (none)"),
+                    }
                 }
                 cmd if cmd.starts_with("move") => {
                     let mut mv = cmd.split_whitespace();
@@ -104,14 +703,414 @@ where
                     let Some(mv) = Move::<S>::from_sfen(mv) else {
                         continue;
                     };
-                    let mv = position.make_move(mv);
-                    let _ = dbg!(mv);
+                    let before = position.generate_sfen();
+                    if let Move::Put { to, piece } = mv {
+                        if position.place(piece, to).is_some() {
+                            game.push(mv.to_string(), &before);
+                        }
+                        continue;
+                    }
+                    if position.make_move(mv.clone()).is_ok() {
+                        game.push(mv.to_string(), &before);
+                    }
+                }
+                cmd if cmd.starts_with("savegame") => {
+                    let Some(path) = cmd.split_whitespace().nth(1) else {
+                        println!("info string usage: savegame <file>");
+                        continue;
+                    };
+                    game.result = self.game_result(&position);
+                    game.final_sfen = position.generate_sfen();
+                    let saved = std::fs::OpenOptions::new()
+                        .create(true)
+                        .append(true)
+                        .open(path)
+                        .and_then(|mut file| writeln!(file, "{game}"));
+                    if let Err(err) = saved {
+                        println!("info string savegame failed: {err}");
+                    }
+                }
+                cmd if cmd.starts_with("loadgame") => {
+                    // loadgame <file> [n], games counted from 1
+                    let mut tokens = cmd.split_whitespace().skip(1);
+                    let Some(path) = tokens.next() else {
+                        println!("info string usage: loadgame <file> [n]");
+                        continue;
+                    };
+                    let index = tokens.next().and_then(|n| n.parse().ok()).unwrap_or(1);
+                    let games = match std::fs::read_to_string(path) {
+                        Ok(text) => GameRecord::parse_all(&text),
+                        Err(err) => {
+                            println!("info string loadgame failed: {err}");
+                            continue;
+                        }
+                    };
+                    let loaded = games.and_then(|games| {
+                        let mut record = games
+                            .into_iter()
+                            .nth(usize::saturating_sub(index, 1))
+                            .ok_or_else(|| RecordError::Syntax(format!("game {index}")))?;
+                        let replayed = record.replay::<S, B, A, P>()?;
+                        Ok((record, replayed))
+                    });
+                    match loaded {
+                        Ok((record, replayed)) => {
+                            game = record;
+                            position = replayed;
+                        }
+                        Err(err) => println!("info string loadgame failed: {err}"),
+                    }
                 }
                 _ => (),
             }
         }
     }
 
+    fn set_option(&mut self, name: &str, value: &str) {
+        if name == "EvalFile" {
+            if value.is_empty() || value == "<empty>" {
+                *self.nnue_mut() = None;
+                return;
+            }
+            match Network::load(value, BITBOARD_SIZE) {
+                Ok(network) => *self.nnue_mut() = Some(Nnue::new(Arc::new(network))),
+                Err(err) => println!("info string cannot load {value}: {err}"),
+            }
+        } else if name == "ImbalanceFile" {
+            if value.is_empty() || value == "<empty>" {
+                *self.imbalance_table_mut() = *D::imbalance_table();
+                return;
+            }
+            match ImbalanceTable::load(value) {
+                Ok(table) => *self.imbalance_table_mut() = table,
+                Err(err) => println!("info string cannot load {value}: {err}"),
+            }
+        } else if name == "OwnBook" {
+            self.book_mut().enabled = value == "true";
+        } else if name == "BookFile" {
+            if value.is_empty() || value == "<empty>" {
+                self.book_mut().book = None;
+                return;
+            }
+            match Book::load(value) {
+                Ok(book) if book.files() as usize != FILE => {
+                    println!("info string {value} is for a {0}x{0} board", book.files())
+                }
+                Ok(book) => self.book_mut().book = Some(book),
+                Err(err) => println!("info string cannot load {value}: {err}"),
+            }
+        } else if name == "Skill Level" {
+            let level = value.parse().unwrap_or(skill::MAX_LEVEL);
+            self.skill_mut().level = level.min(skill::MAX_LEVEL);
+        } else if name == "UCI_LimitStrength" {
+            self.skill_mut().limit_strength = value == "true";
+        } else if name == "UCI_Elo" {
+            let elo = value.parse().unwrap_or(skill::MAX_ELO);
+            self.skill_mut().elo = elo.clamp(skill::MIN_ELO, skill::MAX_ELO);
+        } else if name == "Personality" {
+            match Personality::by_name(value) {
+                Some(personality) => *self.personality_mut() = *personality,
+                None => println!("info string unknown personality {value}"),
+            }
+        } else if name == "TablebasePath" {
+            let value = if value == "<empty>" { "" } else { value };
+            match self.load_tablebase(value) {
+                Ok(loaded) => println!("info string loaded {loaded} tables"),
+                Err(err) => println!("info string cannot load {value}: {err}"),
+            }
+        }
+    }
+
+    /// Result of the game at `position`, `Aborted` while it goes on.
+    fn game_result(&self, position: &P) -> GameResult {
+        let stm = position.side_to_move();
+        if !position.is_hand_empty(stm, PieceType::Plinth) {
+            return GameResult::Aborted;
+        }
+        let moves = MoveList::pseudo_legal(position, stm);
+        if moves
+            .iter()
+            .any(|mv| !matches!(self.child_position(position, &mv), Child::Illegal))
+        {
+            GameResult::Aborted
+        } else if position.in_check(stm) {
+            GameResult::winner(stm.flip())
+        } else {
+            GameResult::Draw
+        }
+    }
+
+    /// Move from the opening book when `OwnBook` is on, if it is legal in
+    /// `position`.
+    fn book_move(&mut self, position: &P) -> Option<Move<S>> {
+        let (from, to) = self.book_mut().pick(zobrist::hash(position))?;
+        let mv = Move::new(S::from_index(from)?, S::from_index(to)?);
+        match self.child_position(position, &mv) {
+            Child::Illegal => None,
+            _ => Some(mv),
+        }
+    }
+
+    /// Tablebase score from White's point of view, when the position is in
+    /// a loaded table.
+    fn probe_tablebase(&self, _position: &P) -> Option<i32> {
+        None
+    }
+
+    /// Loads every table in `dir`, an empty path unloads them.
+    fn load_tablebase(&mut self, _dir: &str) -> io::Result<usize> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "no tablebases for this variant",
+        ))
+    }
+
+    /// Tuning samples from gensfens records, read in the variant of
+    /// `position`. Known endgames skip the imbalance term and are left out.
+    fn imbalance_samples(&self, position: &P, records: &[Record]) -> Vec<Sample> {
+        let mut samples = vec![];
+        for record in records {
+            let mut position = position.clone();
+            if position.set_sfen(&record.sfen).is_err() {
+                continue;
+            }
+            let counts = [
+                self.count_material(&position, Color::White),
+                self.count_material(&position, Color::Black),
+            ];
+            if endgame::classify(&counts).is_some() {
+                continue;
+            }
+            let result = match position.side_to_move() {
+                Color::White => record.result,
+                _ => -record.result,
+            };
+            samples.push(Sample {
+                counts,
+                eval: self.evaluate_position(&position)
+                    - self.get_imbalance_table().evaluate(&counts),
+                result: (result as f64 + 1.0) / 2.0,
+            });
+        }
+        samples
+    }
+
+    /// Generates `signature` and the tables its captures reach, on the
+    /// plinths of `position`, into `dir`.
+    fn generate_tablebase(&self, _position: &P, _signature: &str, _dir: &str) -> io::Result<usize> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "no tablebases for this variant",
+        ))
+    }
+
+    /// Plays the move that keeps the tablebase result, fastest mate first.
+    /// `None` when the position or one of its children is not in a table.
+    fn tablebase_root(&self, position: &P) -> Option<SearchResult<S>> {
+        let score = self.probe_tablebase(position)?;
+        let player = position.side_to_move();
+        let mut best: Option<(Move<S>, i32)> = None;
+        for mv in MoveList::pseudo_legal(position, player) {
+            let value = match self.child_position(position, &mv) {
+                Child::Position(child) => self.probe_tablebase(&child)?,
+                Child::Draw => 0,
+                Child::Illegal => continue,
+            };
+            let better = match &best {
+                None => true,
+                Some((_, best)) if player == Color::White => value > *best,
+                Some((_, best)) => value < *best,
+            };
+            if better {
+                best = Some((mv, value));
+            }
+        }
+        Some(SearchResult {
+            best_move: best.map(|(mv, _)| mv),
+            score,
+            depth: 0,
+            nodes: 0,
+        })
+    }
+
+    /// Iterative deepening until `limits` are reached. An iteration that was
+    /// cut short by the node or time limit is thrown away. Below full
+    /// strength the limits are capped and the move is picked among the best
+    /// few of the last iteration.
+    fn search(&mut self, position: &P, limits: SearchLimits) -> SearchResult<S> {
+        let limits = self.get_skill().limits(limits, D::skill_caps());
+        let multi_pv = self.get_skill().candidates();
+        self.skill_mut().new_search();
+        let max_depth = limits.max_depth();
+        *self.search_state_mut() = SearchState::new(limits);
+        if let Some(result) = self.tablebase_root(position)
+            && let Some(best_move) = &result.best_move
+        {
+            self.update_best_move(best_move.clone());
+            return result;
+        }
+        self.nnue_refresh(position);
+
+        let mut result = SearchResult {
+            best_move: None,
+            score: 0,
+            depth: 0,
+            nodes: 0,
+        };
+        let player = position.side_to_move();
+        let mut candidates = vec![];
+        for depth in 1..=max_depth {
+            let searched = self.search_root(position, depth, result.best_move.clone(), multi_pv);
+            if self.get_search_state().stopped && result.best_move.is_some() {
+                break;
+            }
+            (result.best_move, result.score) = match searched.first() {
+                Some((mv, score)) => (Some(mv.clone()), *score),
+                None if player == Color::White => (None, i32::MIN),
+                None => (None, i32::MAX),
+            };
+            result.depth = depth;
+            candidates = searched;
+            if self.get_search_state().stopped {
+                break;
+            }
+        }
+        result.nodes = self.get_search_state().nodes;
+        if self.get_skill().enabled()
+            && let Some((mv, score)) = self.skill_mut().pick(&candidates, player)
+        {
+            result.best_move = Some(mv);
+            result.score = score;
+        }
+        if result.best_move.is_none() {
+            let moves = MoveList::pseudo_legal(position, position.side_to_move());
+            result.best_move = moves
+                .into_iter()
+                .find(|mv| !matches!(self.child_position(position, mv), Child::Illegal));
+        }
+        if let Some(best_move) = &result.best_move {
+            self.update_best_move(best_move.clone());
+        }
+        result
+    }
+
+    /// Root moves with the `multi_pv` best scores, best first. The window
+    /// stays open until that many moves are searched, so only their scores
+    /// are exact.
+    fn search_root(
+        &mut self,
+        position: &P,
+        depth: i32,
+        previous_best: Option<Move<S>>,
+        multi_pv: usize,
+    ) -> Vec<(Move<S>, i32)> {
+        let player = position.side_to_move();
+        let mut moves: Vec<Move<S>> = MoveList::pseudo_legal(position, player)
+            .into_iter()
+            .collect();
+        // Search the best move of the previous iteration first.
+        if let Some(index) = moves.iter().position(|m| Some(m) == previous_best.as_ref()) {
+            let mv = moves.remove(index);
+            moves.insert(0, mv);
+        }
+
+        let (mut alpha, mut beta) = (i32::MIN, i32::MAX);
+        let mut best: Vec<(Move<S>, i32)> = vec![];
+        for mv in moves {
+            let value = match self.child_position(position, &mv) {
+                Child::Position(position2) => {
+                    self.nnue_push(position, &position2, &mv);
+                    let value =
+                        self.alpha_beta_search(&position2, depth - 1, alpha, beta, player.flip());
+                    self.nnue_pop();
+                    value
+                }
+                Child::Draw => 0,
+                Child::Illegal => continue,
+            };
+            if self.get_search_state().stopped {
+                break;
+            }
+
+            let better = |(_, other): &(Move<S>, i32)| match player {
+                Color::White => value > *other,
+                _ => value < *other,
+            };
+            let index = best.iter().position(better).unwrap_or(best.len());
+            best.insert(index, (mv, value));
+            best.truncate(multi_pv);
+            if best.len() == multi_pv {
+                let bound = best[multi_pv - 1].1;
+                match player {
+                    Color::White => alpha = bound,
+                    _ => beta = bound,
+                }
+            }
+        }
+        best
+    }
+
+    /// Position after `mv`. Moves come from the pseudo-legal generator, so
+    /// this is where legality is checked: the move is applied to a copy
+    /// and rejected if it leaves the mover's king attacked. The crate's
+    /// `make_move` is not used here, as it checks the move against the
+    /// legal-move cache and rebuilds that cache for the child.
+    fn child_position(&self, position: &P, mv: &Move<S>) -> Child<P> {
+        let Some((from, to)) = mv.info() else {
+            return Child::Illegal;
+        };
+        let player = position.side_to_move();
+        let Some(moved) = *position.piece_at(from) else {
+            return Child::Illegal;
+        };
+        if moved.color != player {
+            return Child::Illegal;
+        }
+        let promoted = moved.piece_type == PieceType::Pawn && to.in_promotion_zone(player);
+        let placed = match promoted {
+            true => match moved.promote() {
+                Some(placed) => placed,
+                None => return Child::Illegal,
+            },
+            false => moved,
+        };
+        let captured = *position.piece_at(to);
+        let mut child = position.clone();
+        let move_data = MoveData::default().promoted(promoted).piece(Some(moved));
+        let move_data =
+            child.update_after_move(from, to, placed, moved, captured, player.flip(), move_data);
+        if child
+            .find_king(player)
+            .is_some_and(|king| move_list::is_attacked(&child, king, player.flip()))
+        {
+            return Child::Illegal;
+        }
+        // Repetitions are found from the position history.
+        child.insert_move(Move::Normal { from, to, placed });
+        child.save_position(Some(move_data));
+        if child.detect_repetition().is_err() || child.detect_insufficient_material().is_err() {
+            return Child::Draw;
+        }
+        Child::Position(child)
+    }
+
+    /// Leaf nodes `depth` plies below `position`, walking the pseudo-legal
+    /// moves that `child_position` accepts. A drawn child ends the game, so
+    /// it only counts as a leaf at the last ply.
+    fn perft(&self, position: &P, depth: u32) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+        MoveList::pseudo_legal(position, position.side_to_move())
+            .into_iter()
+            .map(|mv| match self.child_position(position, &mv) {
+                Child::Position(child) => self.perft(&child, depth - 1),
+                Child::Draw => u64::from(depth == 1),
+                Child::Illegal => 0,
+            })
+            .sum()
+    }
+
     fn alpha_beta_search(
         &mut self,
         position: &P,
@@ -120,6 +1119,12 @@ where
         mut beta: i32,
         player: Color,
     ) -> i32 {
+        if self.search_state_mut().visit() {
+            return 0;
+        }
+        if let Some(score) = self.probe_tablebase(position) {
+            return score;
+        }
         if depth == 0 {
             let m = self.quiescence_search(position, alpha, beta, player);
             if let Some(m) = m.1 {
@@ -127,36 +1132,34 @@ where
             }
             return m.0;
         }
-        let moves = position.legal_moves(player);
+        let moves = MoveList::pseudo_legal(position, player);
+        let mut searched = false;
 
-        if moves.is_empty() {
-            return if position.in_check(player) {
-                // Checkmate - return a null move BUT with mate score
-                if player == Color::White {
-                    i32::MIN
-                } else {
-                    i32::MAX
-                }
-            } else {
-                // Stalemate
-                0
-            };
-        }
-
-        let moves = self.generate_list_of_moves(moves);
-
-        if player == Color::White {
+        let best_value = if player == Color::White {
             let mut best_value = i32::MIN;
 
             for mov in moves {
                 // Make the move
-                let mut position2 = position.clone();
-                // self.update_last_move(mov.clone());
                 let best_move = mov.clone();
-                let _ = position2.make_move(mov);
-
-                // Recursively search
-                let value = self.alpha_beta_search(position, depth - 1, alpha, beta, Color::Black);
+                let value = match self.child_position(position, &mov) {
+                    Child::Position(position2) => {
+                        self.nnue_push(position, &position2, &best_move);
+
+                        // Recursively search
+                        let value = self.alpha_beta_search(
+                            &position2,
+                            depth - 1,
+                            alpha,
+                            beta,
+                            Color::Black,
+                        );
+                        self.nnue_pop();
+                        value
+                    }
+                    Child::Draw => 0,
+                    Child::Illegal => continue,
+                };
+                searched = true;
 
                 // Update best value
                 best_value = best_value.max(value);
@@ -176,13 +1179,26 @@ where
 
             for mov in moves {
                 // Make the move
-                let mut position2 = position.clone();
-                // self.update_last_move(mov.clone());
                 let best_move = mov.clone();
-                let _ = position2.make_move(mov);
-
-                // Recursively search
-                let value = self.alpha_beta_search(position, depth - 1, alpha, beta, Color::White);
+                let value = match self.child_position(position, &mov) {
+                    Child::Position(position2) => {
+                        self.nnue_push(position, &position2, &best_move);
+
+                        // Recursively search
+                        let value = self.alpha_beta_search(
+                            &position2,
+                            depth - 1,
+                            alpha,
+                            beta,
+                            Color::White,
+                        );
+                        self.nnue_pop();
+                        value
+                    }
+                    Child::Draw => 0,
+                    Child::Illegal => continue,
+                };
+                searched = true;
 
                 // Update best value
                 best_value = best_value.min(value);
@@ -197,12 +1213,56 @@ where
                 }
             }
             best_value
+        };
+
+        if searched {
+            best_value
+        } else if position.in_check(player) {
+            // Checkmate - return a null move BUT with mate score
+            if player == Color::White {
+                i32::MIN
+            } else {
+                i32::MAX
+            }
+        } else {
+            // Stalemate
+            0
         }
     }
 
     fn update_last_move(&mut self, mv: Move<S>);
     fn update_best_move(&mut self, mv: Move<S>);
     fn get_best_move(&self) -> Option<Move<S>>;
+    fn get_nnue(&self) -> Option<&Nnue>;
+    fn nnue_mut(&mut self) -> &mut Option<Nnue>;
+    fn get_search_state(&self) -> &SearchState;
+    fn search_state_mut(&mut self) -> &mut SearchState;
+    fn get_pawn_table(&self) -> &PawnTable;
+    fn get_imbalance_table(&self) -> &ImbalanceTable;
+    fn imbalance_table_mut(&mut self) -> &mut ImbalanceTable;
+    fn book_mut(&mut self) -> &mut BookState;
+    fn get_skill(&self) -> &SkillState;
+    fn skill_mut(&mut self) -> &mut SkillState;
+    fn get_personality(&self) -> &Personality;
+    fn personality_mut(&mut self) -> &mut Personality;
+
+    fn nnue_refresh(&mut self, position: &P) {
+        if let Some(nnue) = self.nnue_mut() {
+            nnue.refresh(position);
+        }
+    }
+
+    fn nnue_push(&mut self, parent: &P, child: &P, mv: &Move<S>) {
+        if let Some(nnue) = self.nnue_mut() {
+            nnue.push(parent, child, mv);
+        }
+    }
+
+    fn nnue_pop(&mut self) {
+        if let Some(nnue) = self.nnue_mut() {
+            nnue.pop();
+        }
+    }
 
     // fn alpha_beta_search(
     //     &self,
@@ -285,8 +1345,7 @@ where
             return 0;
         };
 
-        10 * D::get_piece_value(to.piece_type, to.color)
-            - D::get_piece_value(from.piece_type, from.color)
+        10 * D::get_piece_value(to.piece_type) - D::get_piece_value(from.piece_type)
 
         // Killer moves, history heuristic, etc.
         // 0
@@ -302,8 +1361,9 @@ where
         let mut piece_counts = [0; 9];
         let player = position.player_bb(color);
         for pt in PieceTypeIter::default() {
+            // The fairy pieces come after the plinth.
             if pt == PieceType::Plinth {
-                break;
+                continue;
             }
             let bb = position.type_bb(&pt) & &player;
             piece_counts[pt.index()] = bb.len();
@@ -318,13 +1378,13 @@ where
                 phase += count as i32 * D::phase_weight(piece);
             }
         }
-        cmp::min(phase, self.midgame_min().1)
+        cmp::min(phase, D::midgame_min().1)
     }
 
     fn material_balance(&self, piece_counts: &[[u32; 9]; 2], game_phase: i32) -> i32 {
         let mut material = [0, 0];
         let game_phase = {
-            if game_phase > self.midgame_min().0 {
+            if game_phase > D::midgame_min().0 {
                 D::get_piece_value
             } else {
                 D::get_endgame_piece_value
@@ -333,11 +1393,11 @@ where
         for color in [Color::White, Color::Black] {
             for pt in PieceTypeIter::default() {
                 if pt == PieceType::Plinth {
-                    break;
+                    continue;
                 }
 
-                let value = game_phase(pt, color);
-                piece_counts[color.index()][pt.index()] as i32;
+                let value = self.get_personality().piece_value(pt, game_phase(pt))
+                    * piece_counts[color.index()][pt.index()] as i32;
                 material[color.index()] += value;
             }
         }
@@ -348,7 +1408,7 @@ where
         let mut score = 0;
 
         let pst = {
-            if game_phase > self.midgame_min().0 {
+            if game_phase > D::midgame_min().0 {
                 D::get_pst_value
             } else {
                 D::get_pst_endgame_value
@@ -400,30 +1460,31 @@ where
         isolated
     }
 
-    fn count_passed_pawns(
-        &self,
-        pawns: [B; 2],
-        position: &P,
-        passed_bb: [[B; BITBOARD_SIZE]; 2],
-        color: Color,
-    ) -> i32 {
+    fn count_passed_pawns(&self, pawns: [B; 2], color: Color) -> i32 {
         let mut passed_pawn_bonus = 0;
-        let mut passed_pawn_count = 0;
-        let enemy = position.player_bb(color.flip());
-        let index = color.index();
+        let mut passed = 0;
+        let enemy = pawns[color.flip().index()];
         for pawn in pawns[color.index()] {
-            let files = passed_bb[index][pawn.index()];
-            if (files & &enemy).is_empty() {
-                passed_pawn_count += 1;
-
+            if (D::passed_pawn_mask(pawn, color) & &enemy).is_empty() {
+                passed += 1;
                 passed_pawn_bonus += self.passed_pawn_bonus(pawn, color);
             }
         }
 
-        (passed_pawn_count * 10) + passed_pawn_bonus
+        (passed * 10) + passed_pawn_bonus
     }
 
     fn pawn_structure_evaluation(&self, position: &P) -> i32 {
+        self.pawn_entry(position).score
+    }
+
+    /// Pawn-structure score, cached by pawn hash.
+    fn pawn_entry(&self, position: &P) -> PawnEntry {
+        let key = zobrist::pawn_hash(position);
+        if let Some(entry) = self.get_pawn_table().probe(key) {
+            return entry;
+        }
+
         let mut score = 0;
         let pawns = [
             (position.player_bb(Color::White) & &position.type_bb(&PieceType::Pawn)),
@@ -439,26 +1500,16 @@ where
         score += 20 * self.count_isolated_pawns(pawns[1]);
 
         // Passed pawns bonus
-        score += 30
-            * self.count_passed_pawns(
-                pawns,
-                position,
-                self.generate_passed_pawns_bb(),
-                Color::White,
-            );
-        score -= 30
-            * self.count_passed_pawns(
-                pawns,
-                position,
-                self.generate_passed_pawns_bb(),
-                Color::Black,
-            );
+        score += 30 * self.count_passed_pawns(pawns, Color::White);
+        score -= 30 * self.count_passed_pawns(pawns, Color::Black);
 
         // Pawn chains bonus
         score += 15 * self.count_pawn_chains(pawns[0], position, Color::White);
         score -= 15 * self.count_pawn_chains(pawns[1], position, Color::Black);
 
-        score
+        let entry = PawnEntry { key, score };
+        self.get_pawn_table().store(entry);
+        entry
     }
 
     fn count_attacks(
@@ -487,7 +1538,13 @@ where
 
         let mut total_bonus = 0;
 
-        for pawn in pawns {
+        // Each chain is scored from its rearmost pawn, the same for both colours.
+        let mut ordered: Vec<S> = pawns.collect();
+        ordered.sort_by_key(|sq| match color {
+            Color::Black => (FILE as u8 - 1 - sq.rank(), sq.file()),
+            _ => (sq.rank(), sq.file()),
+        });
+        for pawn in ordered {
             let counter = self.count_attacks(visited, pawn, 0, color, pawns);
             visited = counter.0;
             if counter.1 > 1 {
@@ -516,10 +1573,8 @@ where
             if game_phase == 0 && king.file() > RANK as u8 - 2 {
                 return 25;
             }
-        } else if color == Color::Black {
-            if game_phase == 0 && king.file() < 2 {
-                return 25;
-            }
+        } else if color == Color::Black && game_phase == 0 && king.file() < 2 {
+            return 25;
         }
         let enemy_pawns = position.player_bb(color.flip()) & &position.type_bb(&PieceType::Pawn);
 
@@ -536,27 +1591,29 @@ where
         storm.len() as i32 * RANK as i32
     }
 
+    /// Whether a plinth stands directly in front of the king.
     fn king_behind_plinth(&self, position: &P, color: Color) -> bool {
-        let plinths = position.type_bb(&PieceType::Plinth);
-        let king = position.find_king(color).unwrap();
-        let mut behind = plinths & &king;
-        if behind.is_empty() {
+        let plinths = position.player_bb(Color::NoColor);
+        let Some(king) = position.find_king(color) else {
             return false;
-        }
-        if color == Color::White {
-            let sq = behind.pop_reverse().unwrap();
-            sq == king
+        };
+        let front = if color == Color::White {
+            king.rank() as i8 + 1
         } else {
-            let sq = behind.pop().unwrap();
-            sq == king
+            king.rank() as i8 - 1
+        };
+        if !(0..FILE as i8).contains(&front) {
+            return false;
         }
+        let front = D::get_rank(front as u8) & &D::get_file(king.file());
+        (front & &plinths).is_any()
     }
 
     fn attacker_weight(&self, piece: PieceType, position: &P, sq: S) -> i32 {
         match piece {
             PieceType::Queen => 5,  // Most dangerous attacker (multiple directions)
             PieceType::Rook => 3,   // Dangerous file/rank attacks
-            PieceType::Bishop => 2, // Diagonal attacks        PieceType::Knight => 2,     // Tricky jumping attacks
+            PieceType::Bishop => 2, // Diagonal attacks
             PieceType::Pawn => 1,   // Least dangerous but still threatening
             PieceType::Chancellor => {
                 if (position.type_bb(&PieceType::Plinth) & &sq).is_any() {
@@ -582,45 +1639,75 @@ where
         }
     }
 
-    fn proximity_factor(&self, distance: i32) -> i32 {
-        match distance {
-            1 => 5, // Direct contact
-            2 => 4,
-            3 => 3,
-            4 => 2,
-            _ => 1, // Distant pieces matter less
+    /// King square, its neighbours and the rank in front of them.
+    fn king_zone(&self, position: &P, color: Color) -> B {
+        let king = position.find_king(color).unwrap();
+        let around = A::get_non_sliding_attacks(PieceType::King, &king, color, B::empty());
+        let mut zone = around | &king;
+        let front = if color == Color::White {
+            king.rank() as i8 + 2
+        } else {
+            king.rank() as i8 - 2
+        };
+        if (0..FILE as i8).contains(&front) {
+            let files = D::get_neighbor_files(king.file()) | &D::get_file(king.file());
+            zone |= &(D::get_rank(front as u8) & &files);
         }
+        zone
     }
 
-    fn king_attackers_penalty(&self, position: &P, color: Color) -> i32 {
-        let enemy_moves = position.enemy_moves(color);
-        let enemies = position.player_bb(color.flip());
+    /// Squares from which a piece of type `piece_type` would check the king.
+    fn check_squares(&self, position: &P, color: Color, piece_type: PieceType) -> B {
         let king = position.find_king(color).unwrap();
-        let mut penalty = 0;
+        let blockers = position.occupied_bb() | &position.player_bb(Color::NoColor);
+        let piece = Piece { piece_type, color };
+        position.get_moves(&king, &piece, blockers)
+    }
+
+    fn safe_check_weight(&self, piece: PieceType) -> i32 {
+        match piece {
+            PieceType::Queen | PieceType::Chancellor | PieceType::ArchBishop => 6,
+            PieceType::Rook => 5,
+            PieceType::Bishop | PieceType::Knight | PieceType::Giraffe => 3,
+            _ => 0,
+        }
+    }
+
+    /// Danger for the king of `color`: attack units from every enemy piece
+    /// hitting the king zone plus safe checks, looked up in `KING_DANGER`.
+    fn king_attackers_penalty(&self, position: &P, color: Color) -> i32 {
+        let them = color.flip();
+        let zone = self.king_zone(position, color);
+        let plinths = position.player_bb(Color::NoColor);
+        let blockers = position.occupied_bb() | &plinths;
+        let defended = position.enemy_moves(them);
         let mut attackers = 0;
-        for enemy in enemies {
-            let piece = position.piece_at(enemy).unwrap();
-            let distance = A::between(king, king);
-            if (distance & &enemy_moves).is_any() {
-                penalty += self.attacker_weight(piece.piece_type, position, enemy)
-                    * self.proximity_factor(distance.len() as i32);
+        let mut units = 0;
+        for sq in position.player_bb(them) {
+            let Some(piece) = *position.piece_at(sq) else {
+                continue;
+            };
+            if piece.piece_type == PieceType::King {
+                continue;
+            }
+            let attacks = position.get_moves(&sq, &piece, blockers);
+            let hits = (attacks & &zone).len() as i32;
+            if hits > 0 {
                 attackers += 1;
+                units += self.attacker_weight(piece.piece_type, position, sq) * hits;
             }
-        }
-        let defenders = (enemy_moves & &position.player_bb(color)).len() as i32;
-        penalty * self.safety_factor(attackers, defenders)
-    }
 
-    fn safety_factor(&self, attackers_count: i32, defenders_count: i32) -> i32 {
-        // Exponential penalty for multiple attackers
-        match attackers_count.saturating_sub(defenders_count) {
-            0 => 1,
-            1 => 2,
-            2 => 4,
-            3 => 8,
-            4 => 16,
-            _ => 32,
+            let mut targets = attacks & &!position.player_bb(them) & &!defended;
+            if !JUMPERS.contains(&piece.piece_type) {
+                targets &= &!plinths;
+            }
+            let checks = self.check_squares(position, color, piece.piece_type) & &targets;
+            units += self.safe_check_weight(piece.piece_type) * checks.len() as i32;
+        }
+        if attackers < 2 {
+            return 0;
         }
+        KING_DANGER[(units as usize).min(KING_DANGER.len() - 1)]
     }
 
     fn pawn_chain_bonus(&self, pawn: S, color: Color, _position: &P, pawns: B) -> i32 {
@@ -636,91 +1723,101 @@ where
         bonus
     }
 
-    fn generate_list_of_moves(&self, legal_moves: HashMap<S, B>) -> Vec<Move<S>> {
-        let mut moves = vec![];
-        for (sq, _moves) in legal_moves {
-            let from = sq;
-            for to in _moves {
-                let m = Move::new(from, to);
-                moves.push(m);
-            }
-        }
-        moves
-    }
-
-    fn generate_passed_pawns_bb(&self) -> [[B; BITBOARD_SIZE]; 2] {
-        let mut all = [[B::empty(); BITBOARD_SIZE]; 2];
+    /// Safe squares per piece from pseudo-legal attacks. Squares attacked by
+    /// enemy pawns don't count. Jumpers can land on plinths, where sliders
+    /// can't reach them, so those squares count twice.
+    fn mobility_evaluation(&self, position: &P, game_phase: i32) -> i32 {
+        let endgame = game_phase <= D::midgame_min().0;
+        let plinths = position.player_bb(Color::NoColor);
+        let blockers = position.occupied_bb() | &plinths;
+        let pawns = position.type_bb(&PieceType::Pawn);
+        let mut mobility = [0, 0];
         for color in [Color::White, Color::Black] {
-            for sq in S::iter() {
-                if sq.rank() == 0 || sq.rank() == RANK as u8 {
+            let them = color.flip();
+            let mut pawn_attacks = B::empty();
+            for pawn in position.player_bb(them) & &pawns {
+                pawn_attacks |=
+                    &A::get_non_sliding_attacks(PieceType::Pawn, &pawn, them, B::empty());
+            }
+            let unsafe_squares = position.player_bb(color) | &pawn_attacks;
+            for sq in position.player_bb(color) {
+                let Some(piece) = *position.piece_at(sq) else {
+                    continue;
+                };
+                let weight = D::mobility_weight(piece.piece_type, endgame);
+                if weight == 0 {
                     continue;
                 }
-                let mut file = D::get_neighbor_files(sq.file()) | &D::get_file(sq.file());
-                let to = {
-                    if color == Color::Black {
-                        file.pop().unwrap()
-                    } else {
-                        file.pop_reverse().unwrap()
-                    }
+                let moves = position.get_moves(&sq, &piece, blockers) & &!unsafe_squares;
+                let count = if JUMPERS.contains(&piece.piece_type) {
+                    moves.len() + (moves & &plinths).len()
+                } else {
+                    (moves & &!plinths).len()
                 };
-                let range = A::between(sq, to) | &to;
-                all[color.index()][sq.index() as usize] = range;
+                mobility[color.index()] += count as i32 * weight;
             }
         }
-        all
+        mobility[0] - mobility[1]
     }
 
-    fn mobility_evaluation(&self, position: &P, game_phase: i32) -> i32 {
-        let mut mobility = [0, 0];
+    /// Plinth control and outposts for jumpers, slider rays and pawns stuck
+    /// behind plinths, and plinths sheltering the king.
+    fn plinth_evaluation(&self, position: &P) -> i32 {
+        let plinths = position.player_bb(Color::NoColor);
+        if plinths.is_empty() {
+            return 0;
+        }
+        let blockers = position.occupied_bb() | &plinths;
+        let mut score = [0, 0];
         for color in [Color::White, Color::Black] {
-            let legal_moves = position.legal_moves(color);
-            for sq in legal_moves {
-                let Some(piece) = position.piece_at(sq.0) else {
+            let own = position.player_bb(color);
+            let side = &mut score[color.index()];
+            for sq in own {
+                let Some(piece) = *position.piece_at(sq) else {
                     continue;
                 };
-                let moves = sq.1;
-                let attack_plinth = (position.type_bb(&PieceType::Plinth) & &moves).is_any();
-
-                let mobility_weight = match (piece.piece_type, game_phase > self.midgame_min().0) {
-                    (PieceType::Queen, true) => 4, // Queen mobility more important in midgame
-                    (PieceType::Knight, false) => {
-                        // Knight mobility more important in endgame
-                        if attack_plinth { 3 } else { 2 }
-                    }
-                    (PieceType::Chancellor, true) | (PieceType::ArchBishop, true) => {
-                        if attack_plinth {
-                            5
-                        } else {
-                            4
+                match piece.piece_type {
+                    pt if JUMPERS.contains(&pt) => {
+                        let attacks = position.get_moves(&sq, &piece, blockers);
+                        let controlled = attacks & &plinths & &!own;
+                        *side += controlled.len() as i32 * D::plinth_weight(PlinthTerm::Control);
+                        if (plinths & &sq).is_any() {
+                            *side += D::plinth_weight(PlinthTerm::Outpost);
                         }
                     }
-                    (PieceType::Chancellor, false) | (PieceType::ArchBishop, false) => {
-                        if attack_plinth {
-                            4
-                        } else {
-                            3
+                    PieceType::Queen | PieceType::Rook | PieceType::Bishop => {
+                        let attacks = position.get_moves(&sq, &piece, blockers);
+                        let blocked = (attacks & &plinths).len() as i32;
+                        *side -= blocked * D::plinth_weight(PlinthTerm::BlockedSlider);
+                    }
+                    PieceType::Pawn => {
+                        let ahead = D::passed_pawn_mask(sq, color) & &D::get_file(sq.file());
+                        if (ahead & &plinths).is_any() {
+                            *side -= D::plinth_weight(PlinthTerm::BlockedPawn);
                         }
                     }
-                    _ => 1,
-                };
-                mobility[color.index()] = moves.len() as i32 * mobility_weight;
+                    _ => (),
+                }
+            }
+            if self.king_behind_plinth(position, color) {
+                *side += D::plinth_weight(PlinthTerm::KingShelter);
             }
         }
-        (mobility[0] - mobility[1]) / 2
+        score[0] - score[1]
     }
 
     fn king_safety_evaluation(&self, position: &P, game_phase: i32) -> i32 {
-        if game_phase <= self.midgame_min().0 {
+        if game_phase <= D::midgame_min().0 {
             return 0;
         }
 
         let mut score = 0;
 
-        score -= self.king_shelter_penalty(&position, Color::White);
-        score -= self.king_attackers_penalty(&position, Color::Black);
+        score -= self.king_shelter_penalty(position, Color::White);
+        score -= self.king_attackers_penalty(position, Color::White);
 
-        score += self.king_attackers_penalty(&position, Color::Black);
-        score += self.king_shelter_penalty(&position, Color::White);
+        score += self.king_shelter_penalty(position, Color::Black);
+        score += self.king_attackers_penalty(position, Color::Black);
         score
     }
 
@@ -773,12 +1870,81 @@ where
         score
     }
 
+    /// Chancellors on open files, Archbishop diagonals, Giraffe forks and
+    /// traps, and hybrid imbalances.
+    fn fairy_evaluation(&self, position: &P) -> i32 {
+        let plinths = position.player_bb(Color::NoColor);
+        let blockers = position.occupied_bb() | &plinths;
+        let pawns = position.type_bb(&PieceType::Pawn);
+        let mut score = [0, 0];
+        for color in [Color::White, Color::Black] {
+            let them = color.flip();
+            let own = position.player_bb(color);
+            let enemy = position.player_bb(them);
+            let side = &mut score[color.index()];
+
+            for sq in own & &position.type_bb(&PieceType::Chancellor) {
+                let file = D::get_file(sq.file());
+                if (file & &pawns).is_empty() {
+                    *side += D::fairy_weight(FairyTerm::ChancellorOpenFile);
+                } else if (file & &pawns & &own).is_empty() {
+                    *side += D::fairy_weight(FairyTerm::ChancellorHalfOpenFile);
+                }
+            }
+
+            // Diagonal squares reached in the enemy half.
+            for sq in own & &position.type_bb(&PieceType::ArchBishop) {
+                let diagonals = A::get_sliding_attacks(PieceType::Bishop, &sq, blockers);
+                let reached = (diagonals & &D::get_player_side(them) & &!own).len() as i32;
+                *side += reached * D::fairy_weight(FairyTerm::ArchbishopDiagonal);
+            }
+
+            let mut pawn_attacks = B::empty();
+            for pawn in enemy & &pawns {
+                pawn_attacks |=
+                    &A::get_non_sliding_attacks(PieceType::Pawn, &pawn, them, B::empty());
+            }
+            let giraffe_value = D::get_piece_value(PieceType::Giraffe);
+            for sq in own & &position.type_bb(&PieceType::Giraffe) {
+                let piece = Piece {
+                    piece_type: PieceType::Giraffe,
+                    color,
+                };
+                let attacks = position.get_moves(&sq, &piece, blockers);
+                let targets = (attacks & &enemy)
+                    .filter(|target| match *position.piece_at(*target) {
+                        Some(p) => {
+                            p.piece_type == PieceType::King
+                                || D::get_piece_value(p.piece_type) > giraffe_value
+                        }
+                        None => false,
+                    })
+                    .count();
+                if targets >= 2 {
+                    *side += D::fairy_weight(FairyTerm::GiraffeFork);
+                }
+                // Long leaps leave few squares near the edges.
+                match (attacks & &!own & &!pawn_attacks).len() {
+                    0 => *side -= D::fairy_weight(FairyTerm::GiraffeTrapped),
+                    1 => *side -= D::fairy_weight(FairyTerm::GiraffeCornered),
+                    _ => (),
+                }
+            }
+
+            let count = |pt: PieceType, bb: B| (bb & &position.type_bb(&pt)).len() as i32;
+            let archbishops = count(PieceType::ArchBishop, own);
+            if archbishops > 0 && count(PieceType::Bishop, enemy) == 0 {
+                *side += archbishops * D::fairy_weight(FairyTerm::ArchbishopNoBishops);
+            }
+            if archbishops + count(PieceType::Chancellor, own) >= 2 {
+                *side += D::fairy_weight(FairyTerm::HybridPair);
+            }
+        }
+        score[0] - score[1]
+    }
+
     fn is_outpost(&self, sq: S, color: Color, position: &P) -> bool {
-        let in_enemy_territory = match color {
-            Color::White => (D::get_player_side(Color::White) & &sq).is_any(),
-            Color::Black => (D::get_player_side(Color::Black) & &sq).is_any(),
-            Color::NoColor => false,
-        };
+        let in_enemy_territory = (D::get_player_side(color.flip()) & &sq).is_any();
         if !in_enemy_territory {
             return false;
         }
@@ -798,62 +1964,97 @@ where
     fn king_shelter_penalty(&self, position: &P, color: Color) -> i32 {
         let mut penalty = 0;
         let king = position.find_king(color).unwrap();
-        let file = king.file();
+        let rank = king.rank();
         let (end, before_end) = {
             if color == Color::White {
-                (king.up_edge(), king.up_edge() - 1)
+                (FILE as u8 - 1, FILE as u8 - 2)
             } else {
                 (0, 1)
             }
         };
-        if file == end || file == before_end {
+        if rank == end || rank == before_end {
             return 20;
         }
         let attacks = A::get_non_sliding_attacks(PieceType::King, &king, color, B::empty());
         let rank_above = {
             if color == Color::White {
-                (file + 1) as usize
+                rank + 1
             } else {
-                (file - 1) as usize
+                rank - 1
             }
         };
         let pawns = position.player_bb(color) & &position.type_bb(&PieceType::Pawn);
-        let rank_above = D::get_rank(rank_above as u8);
+        let rank_above = D::get_rank(rank_above);
         let rank_above = (rank_above & &attacks) & &pawns;
         penalty -= rank_above.len() as i32 * 15;
 
-        let pawns = D::get_file(file) & &pawns;
+        let pawns = D::get_file(king.file()) & &pawns;
         if pawns.is_empty() {
             penalty += 30;
         }
         penalty
     }
 
-    fn evaluate_position(&self, position: &P, color: Color) -> i32 {
-        let mut eval = 0;
+    /// Hand-crafted evaluation from White's point of view.
+    fn evaluate_position(&self, position: &P) -> i32 {
+        let mut eval = D::side_bias();
 
-        let white_material = self.count_material(position, color);
-        let black_material = self.count_material(position, color.flip());
+        let white_material = self.count_material(position, Color::White);
+        let black_material = self.count_material(position, Color::Black);
         let piece_counts = [white_material, black_material];
         let game_phase = self.calculate_game_phase(&piece_counts);
 
         // Material
         eval += self.material_balance(&piece_counts, game_phase);
+        match endgame::classify(&piece_counts) {
+            Some(Endgame::Draw) => return 0,
+            Some(Endgame::Mate(strong)) => {
+                let (Some(king), Some(bare)) = (
+                    position.find_king(strong),
+                    position.find_king(strong.flip()),
+                ) else {
+                    return eval;
+                };
+                let win = KNOWN_WIN + endgame::drive_to_corner(king, bare, FILE as u8);
+                return if strong == Color::White {
+                    eval + win
+                } else {
+                    eval - win
+                };
+            }
+            None => (),
+        }
+        eval += self.get_imbalance_table().evaluate(&piece_counts);
+
+        let personality = self.get_personality();
 
         // Piece-square tables
-        eval += self.pst_evaluation(position, game_phase);
+        let pst = self.pst_evaluation(position, game_phase);
+        eval += personality.scale(EvalGroup::PieceSquares, pst);
 
         // Pawn structure
-        eval += self.pawn_structure_evaluation(position);
+        let pawns = self.pawn_structure_evaluation(position);
+        eval += personality.scale(EvalGroup::PawnStructure, pawns);
 
         // Mobility
-        eval += self.mobility_evaluation(position, game_phase);
+        let mobility = self.mobility_evaluation(position, game_phase);
+        eval += personality.scale(EvalGroup::Mobility, mobility);
 
         // King safety
-        eval += self.king_safety_evaluation(position, game_phase);
+        let king_safety = self.king_safety_evaluation(position, game_phase);
+        eval += personality.scale(EvalGroup::KingSafety, king_safety);
+
+        // Plinths
+        let plinths = self.plinth_evaluation(position);
+        eval += personality.scale(EvalGroup::Plinths, plinths);
+
+        // Fairy pieces
+        let fairy = self.fairy_evaluation(position);
+        eval += personality.scale(EvalGroup::Fairy, fairy);
 
         // Other positional factors
-        eval += self.other_positional_factors(position);
+        let positional = self.other_positional_factors(position);
+        eval += personality.scale(EvalGroup::Positional, positional);
 
         if position.side_to_move() == Color::White {
             eval += 10;
@@ -861,17 +2062,61 @@ where
             eval -= 10;
         }
 
-        eval
+        let strong = if eval >= 0 {
+            Color::White
+        } else {
+            Color::Black
+        };
+        let scale = endgame::scale_factor(
+            &piece_counts,
+            strong,
+            self.opposite_bishops(position),
+            D::get_piece_value,
+        );
+        eval * scale / SCALE_NORMAL
+    }
+
+    /// Both sides have a single bishop, on squares of different colours.
+    fn opposite_bishops(&self, position: &P) -> bool {
+        let bishops = position.type_bb(&PieceType::Bishop);
+        let white = position.player_bb(Color::White) & &bishops;
+        let black = position.player_bb(Color::Black) & &bishops;
+        if white.len() != 1 || black.len() != 1 {
+            return false;
+        }
+        let shade = |bb: B| bb.into_iter().map(|sq| (sq.rank() + sq.file()) % 2).next();
+        shade(white) != shade(black)
+    }
+
+    /// Static evaluation from White's point of view, using the network when
+    /// one is loaded.
+    fn static_evaluation(&self, position: &P) -> i32 {
+        let stm = position.side_to_move();
+        let eval = match self.get_nnue() {
+            Some(nnue) => {
+                let eval = nnue.evaluate(stm);
+                if stm == Color::White { eval } else { -eval }
+            }
+            None => self.evaluate_position(position),
+        };
+        if self.get_skill().enabled() {
+            eval + self.get_skill().noise(zobrist::hash(position))
+        } else {
+            eval
+        }
     }
 
     fn quiescence_search(
-        &self,
+        &mut self,
         position: &P,
         mut alpha: i32,
         mut beta: i32,
         player: Color,
     ) -> (i32, Option<Move<S>>) {
-        let stand_pat = self.evaluate_position(position, position.side_to_move());
+        if self.search_state_mut().visit() {
+            return (0, None);
+        }
+        let stand_pat = self.static_evaluation(position);
 
         if player == Color::White {
             if stand_pat >= beta {
@@ -885,33 +2130,31 @@ where
             beta = beta.min(stand_pat);
         }
 
-        let legal_moves = position.legal_moves(position.side_to_move());
-        let enemy_pieces = position.player_bb(position.side_to_move().flip());
-        let enemy_moves = position.enemy_moves(position.side_to_move());
         let mut captures = vec![];
-        for (piece, moves) in legal_moves {
-            let _captures = moves & &enemy_pieces;
-            // println!("{}", enemy_moves & &_captures);
-            // if (enemy_moves & &_captures).is_any() {
-            //     continue;
-            // }
-            for capture in _captures {
-                let to = capture;
-                let score = self.move_score(piece, to, position);
-                let m = Move::new(piece, to);
-                captures.push((m, score));
-            }
+        for m in MoveList::captures(position, position.side_to_move()) {
+            let Some((piece, to)) = m.info() else {
+                continue;
+            };
+            let score = self.move_score(piece, to, position);
+            captures.push((m, score));
         }
 
         self.order_moves(&mut captures);
         let mut best_move = None;
 
         for mv in captures {
-            let mut new_board = position.clone();
             let z = mv.0.clone();
+            let eval = match self.child_position(position, &z) {
+                Child::Position(new_board) => {
+                    self.nnue_push(position, &new_board, &z);
+                    let eval = self.quiescence_search(&new_board, alpha, beta, player.flip());
+                    self.nnue_pop();
+                    eval
+                }
+                Child::Draw => (0, None),
+                Child::Illegal => continue,
+            };
             best_move = Some(z);
-            let _ = new_board.make_move(mv.0);
-            let eval = self.quiescence_search(&new_board, alpha, beta, player.flip());
 
             if player == Color::White {
                 alpha = alpha.max(eval.0);
@@ -934,8 +2177,8 @@ where
     }
 
     fn evaluate(&self, position: &P) -> i16 {
-        let white_eval = self.count_material(&position, Color::White);
-        let black_eval = self.count_material(&position, Color::Black);
+        let white_eval = self.count_material(position, Color::White);
+        let black_eval = self.count_material(position, Color::Black);
         let evaluation: i16 =
             (white_eval.iter().sum::<u32>() as i16) - (black_eval.iter().sum::<u32>() as i16);
         let perspective = {
@@ -948,13 +2191,258 @@ where
         evaluation * perspective
     }
 
+    /// Drops available to the side to move, in piece type and square order.
+    fn placement_moves(&self, position: &P) -> Vec<Move<S>> {
+        let color = position.side_to_move();
+        let placement = position.get_placement_squares();
+        let mut moves = vec![];
+        for piece_type in PieceTypeIter::default() {
+            let Some(squares) = placement.get(&piece_type.index()) else {
+                continue;
+            };
+            let piece = Piece { piece_type, color };
+            let mut squares: Vec<S> = squares.into_iter().collect();
+            squares.sort_by_key(|sq| sq.index());
+            moves.extend(squares.into_iter().map(|to| Move::Put { to, piece }));
+        }
+        moves
+    }
+
+    /// Deploy-phase evaluation from White's point of view.
+    fn evaluate_placement(&self, position: &P) -> i32 {
+        let mut score = 0;
+        for color in [Color::White, Color::Black] {
+            let sign = if color == Color::White { 1 } else { -1 };
+            score += sign * self.placement_king_safety(position, color);
+            score += sign * self.placement_coordination(position, color);
+        }
+        score + self.mobility_evaluation(position, D::midgame_min().1)
+    }
+
+    /// Plinth and pawn shield in front of the king, minus enemy pieces that
+    /// already aim at the king zone.
+    fn placement_king_safety(&self, position: &P, color: Color) -> i32 {
+        let Some(king) = position.find_king(color) else {
+            return 0;
+        };
+        let mut score = 0;
+        if self.king_behind_plinth(position, color) {
+            score += 25;
+        }
+        let zone = self.king_zone(position, color);
+        let pawns = position.player_bb(color) & &position.type_bb(&PieceType::Pawn);
+        let around = A::get_non_sliding_attacks(PieceType::King, &king, color, B::empty());
+        let forward = if color == Color::White {
+            king.rank() + 1
+        } else {
+            king.rank().saturating_sub(1)
+        };
+        score += ((around & &D::get_rank(forward)) & &pawns).len() as i32 * 15;
+        score += ((zone & &!around) & &pawns).len() as i32 * 5;
+
+        let them = color.flip();
+        let blockers = position.occupied_bb() | &position.player_bb(Color::NoColor);
+        for sq in position.player_bb(them) {
+            let Some(piece) = *position.piece_at(sq) else {
+                continue;
+            };
+            if piece.piece_type == PieceType::King {
+                continue;
+            }
+            let attacks = position.get_moves(&sq, &piece, blockers);
+            if (attacks & &zone).is_any() {
+                score -= self.attacker_weight(piece.piece_type, position, sq) * 4;
+            }
+        }
+        score
+    }
+
+    /// Rewards defended pieces and threats against enemy drops, punishes
+    /// pieces the opponent attacks without a defender.
+    fn placement_coordination(&self, position: &P, color: Color) -> i32 {
+        let them = color.flip();
+        let blockers = position.occupied_bb() | &position.player_bb(Color::NoColor);
+        let attacks_of = |side: Color| {
+            let mut all = B::empty();
+            for sq in position.player_bb(side) {
+                if let Some(piece) = position.piece_at(sq) {
+                    all |= &position.get_moves(&sq, piece, blockers);
+                }
+            }
+            all
+        };
+        let defended = attacks_of(color);
+        let attacked = attacks_of(them);
+
+        let mut score = 0;
+        for sq in position.player_bb(color) {
+            let Some(piece) = *position.piece_at(sq) else {
+                continue;
+            };
+            if piece.piece_type == PieceType::King {
+                continue;
+            }
+            let value = D::get_piece_value(piece.piece_type);
+            let is_defended = (defended & &sq).is_any();
+            if is_defended {
+                score += 5;
+            }
+            if (attacked & &sq).is_any() && !is_defended {
+                score -= value / 8;
+            }
+            let targets = position.get_moves(&sq, &piece, blockers) & &position.player_bb(them);
+            for target in targets {
+                if let Some(enemy) = position.piece_at(target) {
+                    let gain = D::get_piece_value(enemy.piece_type) - value;
+                    score += gain.max(0) / 10;
+                }
+            }
+        }
+        score
+    }
+
+    /// Iterative deepening over drops until `limits` are reached, like
+    /// `search` does for moves. Without limits it searches
+    /// `PLACEMENT_DEPTH` drops deep.
+    fn placement(&mut self, position: &P, limits: SearchLimits) -> SearchResult<S> {
+        let max_depth = match limits {
+            SearchLimits {
+                depth: None,
+                nodes: None,
+                movetime: None,
+            } => PLACEMENT_DEPTH,
+            _ => limits.max_depth(),
+        };
+        *self.search_state_mut() = SearchState::new(limits);
+        let mut result = SearchResult {
+            best_move: None,
+            score: 0,
+            depth: 0,
+            nodes: 0,
+        };
+        let mut last_nodes = 0;
+        for depth in 1..=max_depth {
+            let before = self.get_search_state().nodes;
+            let (best_move, score) = self.placement_search(position, depth);
+            if self.get_search_state().stopped && result.best_move.is_some() {
+                break;
+            }
+            (result.best_move, result.score, result.depth) = (best_move, score, depth);
+            let nodes = self.get_search_state().nodes - before;
+            // Deeper iterations find no more drops once the hands are empty.
+            if self.get_search_state().stopped || nodes == last_nodes {
+                break;
+            }
+            last_nodes = nodes;
+        }
+        result.nodes = self.get_search_state().nodes;
+        result
+    }
+
+    /// Minimax over drops. Returns the best drop and its White-POV score.
+    fn placement_search(&mut self, position: &P, depth: i32) -> (Option<Move<S>>, i32) {
+        let color = position.side_to_move();
+        let moves = self.placement_moves(position);
+        if depth <= 0 || moves.is_empty() {
+            return (None, self.evaluate_placement(position));
+        }
+        let mut best = None;
+        let mut best_score = if color == Color::White {
+            i32::MIN
+        } else {
+            i32::MAX
+        };
+        for mv in moves {
+            let Move::Put { to, piece } = mv else {
+                continue;
+            };
+            let mut child = position.clone();
+            if child.place(piece, to).is_none() {
+                continue;
+            }
+            if self.search_state_mut().visit() {
+                break;
+            }
+            let (_, score) = self.placement_search(&child, depth - 1);
+            if self.get_search_state().stopped {
+                break;
+            }
+            let better = if color == Color::White {
+                score > best_score
+            } else {
+                score < best_score
+            };
+            if better {
+                best_score = score;
+                best = Some(mv);
+            }
+        }
+        (best, best_score)
+    }
+
     fn own_last_move(&self, position: &P) -> Option<Move<S>> {
         let m = position.move_history().last()?;
         Some(m.clone())
     }
 
-    fn midgame_min(&self) -> (i32, i32);
     fn passed_pawn_bonus(&self, pawn: S, color: Color) -> i32;
 
     fn pawn_chain_file_bonus(&self, pawn: S) -> i32;
 }
+
+/// Splits `setoption name <name> value <value>` into its name and value.
+pub fn parse_setoption(cmd: &str) -> (String, String) {
+    let rest = cmd.trim_start_matches("setoption").trim();
+    let rest = rest.strip_prefix("name").unwrap_or(rest).trim();
+    match rest.split_once(" value") {
+        Some((name, value)) => (name.trim().to_string(), value.trim().to_string()),
+        None => (rest.to_string(), String::new()),
+    }
+}
+
+/// Splits `position sfen <board> <stm> <hand> <ply> [moves ...]`.
+pub fn parse_position(cmd: &str) -> Option<(String, Vec<String>)> {
+    let mut tokens = cmd.split_whitespace().skip(1);
+    if tokens.next()? != "sfen" {
+        return None;
+    }
+    let sfen: Vec<&str> = tokens.by_ref().take(4).collect();
+    if sfen.len() != 4 {
+        return None;
+    }
+    let moves = match tokens.next() {
+        Some("moves") => tokens.map(String::from).collect(),
+        _ => vec![],
+    };
+    Some((sfen.join(" "), moves))
+}
+
+/// Same position with colours swapped and the board flipped top to bottom.
+pub fn mirror_sfen(sfen: &str) -> Option<String> {
+    let fields: Vec<&str> = sfen.split_whitespace().collect();
+    let [board, stm, hand, ply] = fields[..] else {
+        return None;
+    };
+    let swap_case = |s: &str| -> String {
+        s.chars()
+            .map(|c| {
+                if c.is_ascii_uppercase() {
+                    c.to_ascii_lowercase()
+                } else {
+                    c.to_ascii_uppercase()
+                }
+            })
+            .collect()
+    };
+    let board: Vec<String> = board.split('/').rev().map(swap_case).collect();
+    let stm = match stm {
+        "w" => "b",
+        "b" => "w",
+        _ => return None,
+    };
+    Some(format!(
+        "{} {stm} {} {ply}",
+        board.join("/"),
+        swap_case(hand)
+    ))
+}
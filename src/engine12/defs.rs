@@ -1,3 +1,6 @@
+use crate::imbalance::ImbalanceTable;
+use crate::skill::SkillCaps;
+
 use shuuro::shuuro12::{
     bitboard12::BB12,
     board_defs::{FILE_BB, RANK_BB},
@@ -5,60 +8,34 @@ use shuuro::shuuro12::{
 };
 
 #[rustfmt::skip]
-pub const PIECE_VALUES: [[i32; 9]; 2] = [
-    [
-        0,    // King (effectively infinite, but using a large number)
-        1100, // Queen
-        600,  // Rook
-        400,  // Bishop
-        350,  // Knight
-        90,   // Pawn
-        1200, // Chancellor (Rook + Knight)
-        950,  // Archbishop (Bishop + Knight)
-        200,  // Giraffe (reduced strength as requested)
-    ],
-    // Black pieces [0][1]
-    [
-        0,    // King
-        1100, // Queen
-        600,  // Rook
-        390,  // Bishop
-        360,  // Knight
-        120,  // Pawn
-        1200, // Chancellor
-        950,  // Archbishop
-        200,  // Giraffe (reduced strength)
-    ],
+pub const PIECE_VALUES: [i32; 9] = [
+    0,    // King (effectively infinite, but using a large number)
+    1100, // Queen
+    600,  // Rook
+    400,  // Bishop
+    350,  // Knight
+    90,   // Pawn
+    1200, // Chancellor (Rook + Knight)
+    950,  // Archbishop (Bishop + Knight)
+    200,  // Giraffe (reduced strength as requested)
 ];
 
 #[rustfmt::skip]
-pub const ENDGAME_PIECE_VALUES: [[i32; 9]; 2] = [
-    // White pieces [1][0]
-    [
-        0,    // King (effectively infinite)
-        1150, // Queen
-        650,  // Rook
-        480,  // Bishop
-        350,  // Knight
-        130,  // Pawn
-        1250, // Chancellor
-        980,  // Archbishop
-        200,  // Giraffe (reduced strength as requested)
-    ],
-    // Black pieces [1][1]
-    [
-        0,    // King
-        1150, // Queen
-        650,  // Rook
-        420,  // Bishop
-        380,  // Knight
-        130,  // Pawn
-        1250, // Chancellor
-        980,  // Archbishop
-        200,  // Giraffe (reduced strength)
-    ],
+pub const ENDGAME_PIECE_VALUES: [i32; 9] = [
+    0,    // King (effectively infinite)
+    1150, // Queen
+    650,  // Rook
+    480,  // Bishop
+    350,  // Knight
+    130,  // Pawn
+    1250, // Chancellor
+    980,  // Archbishop
+    200,  // Giraffe (reduced strength as requested)
 ];
 
+// Added to White's score, keeps any colour preference out of the tables
+pub const SIDE_BIAS: i32 = 0;
+
 #[rustfmt::skip]
 const fn pst() -> [[[i32; 144]; 9]; 2] {
     let mut pst = [[[0; 144]; 9], [[0; 144]; 9]];
@@ -274,10 +251,82 @@ pub const PST: [[[i32; 144]; 9]; 2] = pst();
 #[rustfmt::skip]
 pub const PST_ENDGAME: [[[i32; 144]; 9]; 2] = pst_endgame();
 
+// Mobility weight per safe square, [midgame, endgame]
+#[rustfmt::skip]
+pub const MOBILITY_WEIGHTS: [[i32; 2]; 9] = [
+    [0, 0], // King
+    [1, 2], // Queen
+    [2, 3], // Rook
+    [3, 4], // Bishop
+    [3, 4], // Knight
+    [0, 0], // Pawn
+    [2, 3], // Chancellor
+    [3, 3], // Archbishop
+    [2, 3], // Giraffe
+];
+
+// Plinth term weights, indexed by `PlinthTerm`
+#[rustfmt::skip]
+pub const PLINTH_WEIGHTS: [i32; 5] = [
+    3,  // Control
+    15, // Outpost
+    5,  // BlockedSlider
+    15, // KingShelter
+    8,  // BlockedPawn
+];
+
+// Fairy term weights, indexed by `FairyTerm`. Open files are longer than on
+// 8x8, and an Archbishop reaches about twice as many squares in the enemy
+// half, so each one counts for less.
+#[rustfmt::skip]
+pub const FAIRY_WEIGHTS: [i32; 8] = [
+    20, // ChancellorOpenFile
+    10, // ChancellorHalfOpenFile
+    1,  // ArchbishopDiagonal
+    25, // GiraffeFork
+    40, // GiraffeTrapped
+    20, // GiraffeCornered
+    20, // ArchbishopNoBishops
+    25, // HybridPair
+];
+
+// Second-order material term, rows and columns
+// (King, Queen, Rook, Bishop, Knight, Pawn, Chancellor, Archbishop, Giraffe)
+// Tuned with `tuneimbalance` on 114k quiet positions from depth 1 self-play
+// in Shuuro Fairy. It scored 149 +/- 48 Elo over the hand-set table in 200
+// Shuuro games at 8000 nodes per move.
+#[rustfmt::skip]
+pub const IMBALANCE: ImbalanceTable = ImbalanceTable {
+    ours: [
+        [0,   0,   0,   0,   0,   0,   0,   0,   0],
+        [0, -13,   0,   0,   0,   0,   0,   0,   0],
+        [0, -27, -40,   0,   0,   0,   0,   0,   0],
+        [0,   6,   8,  -4,   0,   0,   0,   0,   0],
+        [0,   8,   6,   0, -16,   0,   0,   0,   0],
+        [0,   7,  -2,  -5,  -8, -14,   0,   0,   0],
+        [0, -21, -11,   5,   4,   9,  -4,   0,   0],
+        [0, -22,  -9, -10,  -3,   0,   2, -13,   0],
+        [0,   2,  -2,  -5, -10, -11,   4,  -4, -24],
+    ],
+    theirs: [
+        [0,   0,   0,   0,   0,   0,   0,   0,   0],
+        [0,   0,   0,   0,   0,   0,   0,   0,   0],
+        [0,   3,   0,   0,   0,   0,   0,   0,   0],
+        [0,  -1,   0,   0,   0,   0,   0,   0,   0],
+        [0,   1,  -4,  -1,   0,   0,   0,   0,   0],
+        [0, -10,  -3,  -3,   0,   0,   0,   0,   0],
+        [0,  -1,   7,   4,   9,   8,   0,   0,   0],
+        [0,  -4,  -2,  -2,   1,  -1,  -4,   0,   0],
+        [0,  -9,  -4,  -5,  -2,  -3,  -8,  -2,   0],
+    ],
+};
+
 #[rustfmt::skip]
 pub const NEIGHBOR_FILES: [BB12<Square12>; 12] = generate_neighbor_files();
 #[rustfmt::skip]
 pub const PLAYER_TERRITORY: [BB12<Square12>; 2] = generate_player_sides();
+#[rustfmt::skip]
+pub const PASSED_PAWNS: [[BB12<Square12>; 144]; 2] = generate_passed_pawns();
 
 #[rustfmt::skip]
 pub const PHASE_WEIGHTS: [i32; 9] = [
@@ -292,6 +341,9 @@ pub const PHASE_WEIGHTS: [i32; 9] = [
     1, // Giraffe
 ];
 
+/// Phase above which midgame values apply, and the cap on the phase.
+pub const MIDGAME_MIN: (i32, i32) = (20, 30);
+
 const fn generate_neighbor_files() -> [BB12<Square12>; 12] {
     let mut files = [BB12::new(0, 0); 12];
     let mut file = 0;
@@ -301,8 +353,8 @@ const fn generate_neighbor_files() -> [BB12<Square12>; 12] {
         } else if file == 11 {
             files[11] = FILE_BB[10];
         } else {
-            let left = FILE_BB[file as usize - 1];
-            let right = FILE_BB[file as usize + 1];
+            let left = FILE_BB[file - 1];
+            let right = FILE_BB[file + 1];
             files[file] = BB12::new(left.0.0 | right.0.0, left.0.1 | right.0.1);
         }
         file += 1;
@@ -314,15 +366,15 @@ const fn generate_neighbor_files() -> [BB12<Square12>; 12] {
 const fn generate_player_sides() -> [BB12<Square12>; 2] {
     let mut white = BB12::new(0, 0);
     let mut black = BB12::new(0, 0);
-    let end = 4;
+    let end = 6;
     let mut current_rank = 0;
     while current_rank < end {
         let rank = RANK_BB[current_rank as usize];
         white = BB12::new(white.0.0 | rank.0.0, white.0.1 | rank.0.1);
         current_rank += 1;
     }
-    let end = 8;
-    let mut current_rank = 4;
+    let end = 12;
+    let mut current_rank = 6;
     while current_rank < end {
         let rank = RANK_BB[current_rank as usize];
         black = BB12::new(black.0.0 | rank.0.0, black.0.1 | rank.0.1);
@@ -330,3 +382,69 @@ const fn generate_player_sides() -> [BB12<Square12>; 2] {
     }
     [white, black]
 }
+
+/// Squares on the pawn's and neighbouring files in front of it, per colour.
+const fn generate_passed_pawns() -> [[BB12<Square12>; 144]; 2] {
+    let mut masks = [[BB12::new(0, 0); 144]; 2];
+    let mut sq = 0;
+    while sq < 144 {
+        let file = sq % 12;
+        let rank = sq / 12;
+        let neighbors = NEIGHBOR_FILES[file];
+        let files = (
+            neighbors.0.0 | FILE_BB[file].0.0,
+            neighbors.0.1 | FILE_BB[file].0.1,
+        );
+        let mut ahead = [(0, 0); 2];
+        let mut current_rank = 0;
+        while current_rank < 12 {
+            let bb = RANK_BB[current_rank].0;
+            if current_rank > rank {
+                ahead[0] = (ahead[0].0 | bb.0, ahead[0].1 | bb.1);
+            } else if current_rank < rank {
+                ahead[1] = (ahead[1].0 | bb.0, ahead[1].1 | bb.1);
+            }
+            current_rank += 1;
+        }
+        masks[0][sq] = BB12::new(files.0 & ahead[0].0, files.1 & ahead[0].1);
+        masks[1][sq] = BB12::new(files.0 & ahead[1].0, files.1 & ahead[1].1);
+        sq += 1;
+    }
+    masks
+}
+
+// Same sections the crate's plinth generator uses.
+pub const PLINTH_SECTIONS: [(u8, u8, u8, u8, u8); 4] = [
+    (0, 6, 0, 6, 2),
+    (0, 6, 6, 12, 2),
+    (6, 12, 0, 6, 2),
+    (6, 12, 6, 12, 2),
+];
+
+pub const START_POSITIONS: [&str; 4] = [
+    "2rnbkqbnr2/12/2pppppppp2/3_.8/9_.2/12/12/1_.10/7_.4/2PPPPPPPP2/12/2RNBKQBNR2 w - 1",
+    "3k1r2q3/1pp2pp2pp1/4b2n4/12/2_.9/8_.3/5_.6/10_.1/12/4B2N4/1PP2PP2PP1/3K1R2Q3 b - 1",
+    "12/12/12/2kr8/4pp6/12/12/12/7RR3/PPPQP4K2/12/12 b - 1",
+    "4q_nk2r1b/2pp2_.5/8_.3/3_.8/12/12/_.11/12/12/10_.1/2P4P3P/2N1_.QKR_.1N1 b - 15",
+];
+
+pub const BENCH_POSITIONS: [&str; 5] = [
+    "2rnbkqbnr2/12/2pppppppp2/3_.8/9_.2/12/12/1_.10/7_.4/2PPPPPPPP2/12/2RNBKQBNR2 w - 1",
+    "3k1r2q3/1pp2pp2pp1/4b2n4/12/2_.9/8_.3/5_.6/10_.1/12/4B2N4/1PP2PP2PP1/3K1R2Q3 b - 1",
+    "12/12/12/2kr8/4pp6/12/12/12/7RR3/PPPQP4K2/12/12 b - 1",
+    "3k1c2a3/1pp2pp2pp1/4g7/12/2_.9/8_.3/5_.6/10_.1/12/4G7/1PP2PP2PP1/3K1C2A3 w - 1",
+    "12/12/12/4k7/3p8/12/12/5P6/4K7/12/12/12 w - 1",
+];
+
+// Depth and node caps of skill levels 0 to 19. Node caps start at 100 and
+// grow by half per level. In `match` runs of 48 Shuuro games per pair,
+// level 4 beat 0 by 191 +/- 88 Elo, 8 beat 4 by 338 +/- 173, 12 beat 8 by
+// 293 +/- 145, 16 beat 12 by 163 +/- 107 and, in 24 games, 19 beat 16 by
+// 104 +/- 145.
+pub const SKILL_CAPS: SkillCaps = SkillCaps {
+    depth: [1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10],
+    nodes: [
+        100, 150, 220, 340, 510, 760, 1140, 1710, 2560, 3840, 5770, 8650, 12970, 19460, 29190,
+        43790, 65680, 98530, 147790, 221680,
+    ],
+};
@@ -1,26 +1,22 @@
+use crate::imbalance::ImbalanceTable;
+use crate::skill::SkillCaps;
+
 use shuuro::shuuro6::{
     bitboard6::BB6,
     board_defs::{FILE_BB, RANK_BB},
     square6::Square6,
 };
 
-// Midgame values
+// Midgame values (King, Queen, Rook, Bishop, Knight, Pawn, Chancellor, Archbishop, Giraffe)
 #[rustfmt::skip]
-pub const PIECE_VALUES: [[i32; 9]; 2] = [
-    // White pieces (King, Queen, Rook, Bishop, Knight, Pawn, Chancellor, Archbishop, Giraffe)
-    [0, 750, 350, 250, 200, 60, 500, 400, 150],
-    // Black pieces (asymmetric for variety)
-    [0, 730, 340, 240, 190, 65, 480, 390, 160],
-];
+pub const PIECE_VALUES: [i32; 9] = [0, 750, 350, 250, 200, 60, 500, 400, 150];
 
 // Endgame values
 #[rustfmt::skip]
-pub const ENDGAME_PIECE_VALUES: [[i32; 9]; 2] = [
-    // White
-    [0, 800, 400, 270, 220, 70, 550, 450, 120],
-    // Black
-    [0, 780, 390, 260, 210, 75, 530, 440, 130],
-];
+pub const ENDGAME_PIECE_VALUES: [i32; 9] = [0, 800, 400, 270, 220, 70, 550, 450, 120];
+
+// Added to White's score, keeps any colour preference out of the tables
+pub const SIDE_BIAS: i32 = 0;
 
 // Midgame Piece-Square Tables
 #[rustfmt::skip]
@@ -183,10 +179,85 @@ pub const PHASE_WEIGHTS: [i32; 9] = [
     1, // Giraffe
 ];
 
+/// Phase above which midgame values apply, and the cap on the phase.
+pub const MIDGAME_MIN: (i32, i32) = (6, 10);
+
+// Mobility weight per safe square, [midgame, endgame]
+#[rustfmt::skip]
+pub const MOBILITY_WEIGHTS: [[i32; 2]; 9] = [
+    [0, 0], // King
+    [1, 2], // Queen
+    [2, 3], // Rook
+    [3, 4], // Bishop
+    [4, 4], // Knight
+    [0, 0], // Pawn
+    [2, 3], // Chancellor
+    [3, 4], // Archbishop
+    [3, 3], // Giraffe
+];
+
+// Plinth term weights, indexed by `PlinthTerm`
+#[rustfmt::skip]
+pub const PLINTH_WEIGHTS: [i32; 5] = [
+    6,  // Control
+    25, // Outpost
+    10, // BlockedSlider
+    25, // KingShelter
+    15, // BlockedPawn
+];
+
+// Fairy term weights, indexed by `FairyTerm`. Files and diagonals are
+// short here and a Giraffe is short of squares almost anywhere, so those
+// terms weigh less than on 8x8.
+#[rustfmt::skip]
+pub const FAIRY_WEIGHTS: [i32; 8] = [
+    10, // ChancellorOpenFile
+    5,  // ChancellorHalfOpenFile
+    3,  // ArchbishopDiagonal
+    25, // GiraffeFork
+    20, // GiraffeTrapped
+    10, // GiraffeCornered
+    20, // ArchbishopNoBishops
+    25, // HybridPair
+];
+
+// Second-order material term, rows and columns
+// (King, Queen, Rook, Bishop, Knight, Pawn, Chancellor, Archbishop, Giraffe)
+// Tuned with `tuneimbalance` on 57k quiet positions from depth 2 self-play
+// in Shuuro Mini Fairy. It scored 49 +/- 40 Elo over the hand-set table in
+// 200 Shuuro Mini games at 8000 nodes per move.
+#[rustfmt::skip]
+pub const IMBALANCE: ImbalanceTable = ImbalanceTable {
+    ours: [
+        [0,   0,   0,   0,   0,   0,   0,   0,   0],
+        [0, -12,   0,   0,   0,   0,   0,   0,   0],
+        [0, -29, -19,   0,   0,   0,   0,   0,   0],
+        [0,   2,  14,   1,   0,   0,   0,   0,   0],
+        [0,   3,  13,   9,   8,   0,   0,   0,   0],
+        [0,   6,   4,   7,  15, -19,   0,   0,   0],
+        [0, -25, -14,   3,   3,   6,  -4,   0,   0],
+        [0, -24,  -9,  -6,   4,   7,   0,  -1,   0],
+        [0,   1,   4,  -4,   1,  -7,   1,   2, -20],
+    ],
+    theirs: [
+        [0,   0,   0,   0,   0,   0,   0,   0,   0],
+        [0,   0,   0,   0,   0,   0,   0,   0,   0],
+        [0,   5,   0,   0,   0,   0,   0,   0,   0],
+        [0,   2,  -4,   0,   0,   0,   0,   0,   0],
+        [0,   8,  -3,   0,   0,   0,   0,   0,   0],
+        [0,  -6, -11, -15, -14,   0,   0,   0,   0],
+        [0,   0,   6,   1,   2,   7,   0,   0,   0],
+        [0,   0,   1,   3,   3,   6,   0,   0,   0],
+        [0,  -4,  -9,  -4,  -7,   3,  -3,  -4,   0],
+    ],
+};
+
 #[rustfmt::skip]
 pub const NEIGHBOR_FILES: [BB6<Square6>; 6] = generate_neighbor_files();
 #[rustfmt::skip]
 pub const PLAYER_TERRITORY: [BB6<Square6>; 2] = generate_player_sides();
+#[rustfmt::skip]
+pub const PASSED_PAWNS: [[BB6<Square6>; 36]; 2] = generate_passed_pawns();
 
 const fn generate_neighbor_files() -> [BB6<Square6>; 6] {
     let mut files = [BB6::new(0); 6];
@@ -197,8 +268,8 @@ const fn generate_neighbor_files() -> [BB6<Square6>; 6] {
         } else if file == 5 {
             files[5] = FILE_BB[4];
         } else {
-            let left = FILE_BB[file as usize - 1];
-            let right = FILE_BB[file as usize + 1];
+            let left = FILE_BB[file - 1];
+            let right = FILE_BB[file + 1];
             files[file] = BB6::new(left.0 | right.0);
         }
         file += 1;
@@ -224,3 +295,60 @@ const fn generate_player_sides() -> [BB6<Square6>; 2] {
     }
     [white, black]
 }
+
+/// Squares on the pawn's and neighbouring files in front of it, per colour.
+const fn generate_passed_pawns() -> [[BB6<Square6>; 36]; 2] {
+    let mut masks = [[BB6::new(0); 36]; 2];
+    let mut sq = 0;
+    while sq < 36 {
+        let file = sq % 6;
+        let rank = sq / 6;
+        let files = NEIGHBOR_FILES[file].0 | FILE_BB[file].0;
+        let mut ahead = [0; 2];
+        let mut current_rank = 0;
+        while current_rank < 6 {
+            if current_rank > rank {
+                ahead[0] |= RANK_BB[current_rank].0;
+            } else if current_rank < rank {
+                ahead[1] |= RANK_BB[current_rank].0;
+            }
+            current_rank += 1;
+        }
+        masks[0][sq] = BB6::new(files & ahead[0]);
+        masks[1][sq] = BB6::new(files & ahead[1]);
+        sq += 1;
+    }
+    masks
+}
+
+// Same sections the crate's plinth generator uses.
+pub const PLINTH_SECTIONS: [(u8, u8, u8, u8, u8); 2] = [(0, 3, 0, 6, 1), (3, 6, 0, 6, 1)];
+
+pub const START_POSITIONS: [&str; 4] = [
+    "rnkqbn/pppppp/2_.3/3_.2/PPPPPP/RNKQBN w - 1",
+    "1kr1b1/ppp1pp/3_.2/1_.4/PP1PPP/1KR1B1 b - 1",
+    "2k1r1/pp1pp1/1n2_.1/1_.2N1/1PP1P1/2K1R1 w - 1",
+    "q1k1b1/pp1ppp/6/2_.3/PPP1PP/1BK1Q1 b - 1",
+];
+
+pub const BENCH_POSITIONS: [&str; 6] = [
+    "rnkqbn/pppppp/2_.3/3_.2/PPPPPP/RNKQBN w - 1",
+    "1kr1b1/ppp1pp/3_.2/1_.4/PP1PPP/1KR1B1 b - 1",
+    "2k1r1/pp1pp1/1n2_.1/1_.2N1/1PP1P1/2K1R1 w - 1",
+    "1kc1a1/ppp1pp/3_.2/1_.4/PP1PPP/1KC1A1 w - 1",
+    "gk2r1/pp2pp/2_.3/3_.2/PP2PP/GK2R1 b - 1",
+    "6/2k3/1p4/4P1/2K3/6 w - 1",
+];
+
+// Depth and node caps of skill levels 0 to 19. Node caps start at 40 and
+// grow by half per level. In `match` runs of 96 Shuuro Mini games per
+// pair, level 4 beat 0 by 206 +/- 53 Elo, 8 beat 4 by 100 +/- 50, 12 beat 8
+// by 93 +/- 63, 16 beat 12 by 120 +/- 66 and 19 beat 16 by 97 +/- 58. With
+// depth caps of 3 and 4 around level 12, 12 beat 8 by only 49 +/- 71.
+pub const SKILL_CAPS: SkillCaps = SkillCaps {
+    depth: [1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10],
+    nodes: [
+        40, 60, 90, 140, 200, 300, 460, 680, 1030, 1540, 2310, 3460, 5190, 7780, 11680, 17520,
+        26270, 39410, 59120, 88670,
+    ],
+};
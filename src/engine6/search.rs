@@ -1,12 +1,34 @@
+use crate::book::BookState;
 use crate::engine::Engine;
 use crate::engine::EngineDefs;
+use crate::engine::FairyTerm;
+use crate::engine::PlinthTerm;
+use crate::engine::SearchState;
+use crate::engine6::defs::BENCH_POSITIONS;
 use crate::engine6::defs::ENDGAME_PIECE_VALUES;
+use crate::engine6::defs::FAIRY_WEIGHTS;
+use crate::engine6::defs::IMBALANCE;
+use crate::engine6::defs::MIDGAME_MIN;
+use crate::engine6::defs::MOBILITY_WEIGHTS;
+use crate::engine6::defs::PASSED_PAWNS;
 use crate::engine6::defs::PHASE_WEIGHTS;
 use crate::engine6::defs::PIECE_VALUES;
+use crate::engine6::defs::PLINTH_SECTIONS;
+use crate::engine6::defs::PLINTH_WEIGHTS;
 use crate::engine6::defs::PST;
 use crate::engine6::defs::PST_ENDGAME;
+use crate::engine6::defs::SIDE_BIAS;
+use crate::engine6::defs::SKILL_CAPS;
+use crate::engine6::defs::START_POSITIONS;
+use crate::imbalance::ImbalanceTable;
+use crate::nnue::Nnue;
+use crate::pawn_table::PawnTable;
+use crate::personality::Personality;
+use crate::skill::{SkillCaps, SkillState};
+use crate::tablebase::{Generator, Tablebase};
 
 use shuuro::Move;
+use shuuro::position::Board;
 use shuuro::{
     Color, PieceType, Square,
     attacks::Attacks,
@@ -22,15 +44,21 @@ use shuuro::{
 use super::defs::NEIGHBOR_FILES;
 use super::defs::PLAYER_TERRITORY;
 
+use std::io;
+
 pub struct Defs6 {}
 
 impl EngineDefs<Square6, BB6<Square6>, 6> for Defs6 {
-    fn get_piece_value(piece_type: PieceType, color: Color) -> i32 {
-        PIECE_VALUES[color.index()][piece_type.index()]
+    fn get_piece_value(piece_type: PieceType) -> i32 {
+        PIECE_VALUES[piece_type.index()]
+    }
+
+    fn get_endgame_piece_value(piece_type: PieceType) -> i32 {
+        ENDGAME_PIECE_VALUES[piece_type.index()]
     }
 
-    fn get_endgame_piece_value(piece_type: PieceType, color: Color) -> i32 {
-        ENDGAME_PIECE_VALUES[color.index()][piece_type.index()]
+    fn side_bias() -> i32 {
+        SIDE_BIAS
     }
 
     fn get_pst_value(square: Square6, piece_type: PieceType, color: Color) -> i32 {
@@ -61,14 +89,62 @@ impl EngineDefs<Square6, BB6<Square6>, 6> for Defs6 {
         PHASE_WEIGHTS[piece_type]
     }
 
+    fn midgame_min() -> (i32, i32) {
+        MIDGAME_MIN
+    }
+
     fn all_files() -> [BB6<Square6>; 6] {
         FILE_BB
     }
+
+    fn mobility_weight(piece_type: PieceType, endgame: bool) -> i32 {
+        MOBILITY_WEIGHTS[piece_type.index()][endgame as usize]
+    }
+
+    fn imbalance_table() -> &'static ImbalanceTable {
+        &IMBALANCE
+    }
+
+    fn plinth_weight(term: PlinthTerm) -> i32 {
+        PLINTH_WEIGHTS[term as usize]
+    }
+
+    fn fairy_weight(term: FairyTerm) -> i32 {
+        FAIRY_WEIGHTS[term as usize]
+    }
+
+    fn passed_pawn_mask(square: Square6, color: Color) -> BB6<Square6> {
+        PASSED_PAWNS[color.index()][square.index()]
+    }
+
+    fn start_positions() -> &'static [&'static str] {
+        &START_POSITIONS
+    }
+
+    fn bench_positions() -> &'static [&'static str] {
+        &BENCH_POSITIONS
+    }
+
+    fn skill_caps() -> &'static SkillCaps {
+        &SKILL_CAPS
+    }
+
+    fn plinth_sections() -> &'static [(u8, u8, u8, u8, u8)] {
+        &PLINTH_SECTIONS
+    }
 }
 
 pub struct Engine6 {
     pub last_move: Option<Move<Square6>>,
     pub best_move: Option<Move<Square6>>,
+    pub nnue: Option<Nnue>,
+    pub search: SearchState,
+    pub pawn_table: PawnTable,
+    pub imbalance: ImbalanceTable,
+    pub book: BookState,
+    pub skill: SkillState,
+    pub personality: Personality,
+    pub tablebase: Tablebase,
 }
 
 impl
@@ -87,10 +163,6 @@ impl
         Attacks6::init();
     }
 
-    fn midgame_min(&self) -> (i32, i32) {
-        (6, 10)
-    }
-
     fn passed_pawn_bonus(&self, pawn: Square6, color: Color) -> i32 {
         match (pawn.rank(), color) {
             (4, Color::White) | (1, Color::Black) => 50, // On 7th rank (about to promote)
@@ -121,10 +193,97 @@ impl
         Self {
             last_move: None,
             best_move: None,
+            nnue: None,
+            search: SearchState::default(),
+            pawn_table: PawnTable::default(),
+            imbalance: IMBALANCE,
+            book: BookState::default(),
+            skill: SkillState::default(),
+            personality: Personality::default(),
+            tablebase: Tablebase::default(),
         }
     }
 
     fn get_best_move(&self) -> Option<Move<Square6>> {
         self.best_move.clone()
     }
+
+    fn get_nnue(&self) -> Option<&Nnue> {
+        self.nnue.as_ref()
+    }
+
+    fn nnue_mut(&mut self) -> &mut Option<Nnue> {
+        &mut self.nnue
+    }
+
+    fn get_search_state(&self) -> &SearchState {
+        &self.search
+    }
+
+    fn search_state_mut(&mut self) -> &mut SearchState {
+        &mut self.search
+    }
+
+    fn get_pawn_table(&self) -> &PawnTable {
+        &self.pawn_table
+    }
+
+    fn get_imbalance_table(&self) -> &ImbalanceTable {
+        &self.imbalance
+    }
+
+    fn imbalance_table_mut(&mut self) -> &mut ImbalanceTable {
+        &mut self.imbalance
+    }
+
+    fn book_mut(&mut self) -> &mut BookState {
+        &mut self.book
+    }
+
+    fn get_skill(&self) -> &SkillState {
+        &self.skill
+    }
+
+    fn skill_mut(&mut self) -> &mut SkillState {
+        &mut self.skill
+    }
+
+    fn get_personality(&self) -> &Personality {
+        &self.personality
+    }
+
+    fn personality_mut(&mut self) -> &mut Personality {
+        &mut self.personality
+    }
+
+    fn probe_tablebase(&self, position: &P6<Square6, BB6<Square6>>) -> Option<i32> {
+        let score = self.tablebase.probe(position)?.score();
+        Some(match position.side_to_move() {
+            Color::Black => -score,
+            _ => score,
+        })
+    }
+
+    fn load_tablebase(&mut self, dir: &str) -> io::Result<usize> {
+        self.tablebase = if dir.is_empty() {
+            Tablebase::default()
+        } else {
+            Tablebase::load(dir)?
+        };
+        Ok(self.tablebase.len())
+    }
+
+    fn generate_tablebase(
+        &self,
+        position: &P6<Square6, BB6<Square6>>,
+        signature: &str,
+        dir: &str,
+    ) -> io::Result<usize> {
+        let signature = signature
+            .parse()
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "bad signature"))?;
+        let mut generator = Generator::new(position.player_bb(Color::NoColor));
+        generator.generate(&signature);
+        generator.write(dir)
+    }
 }
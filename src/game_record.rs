@@ -0,0 +1,532 @@
+use shuuro::{
+    Color, Move, Selection, Square, Variant,
+    attacks::Attacks,
+    bitboard::BitBoard,
+    position::{Board, Placement, Play, Rules, Sfen},
+};
+use std::{fmt, fmt::Display, hash::Hash, str::FromStr};
+
+// PGN-like text format, one game after another:
+//
+//   [Variant "standardFairy"]
+//   [Seed "7"]
+//   [Start "8/8/2_.5/8/8/5_.2/8/8 w - 1"]
+//   [Fight "<deployed position>"]
+//   [Final "<last position>"]
+//   [Result "1-0"]
+//
+//   +Q +R +q +n K@e1 k@e8 Q@d1 q@d8 ...
+//   1. d1_d4 {[%eval 0.35] [%clk 0:04:58]} d8_d5 {[%eval 0.20]} 2. ... 1-0
+//
+// Shop purchases (`+Q`), deploy drops (`Q@d1`) and fight moves (`d1_d4`)
+// are in the crate's sfen move notation and tell themselves apart. Only
+// fight moves are numbered and carry comments: `%eval` in pawns from
+// White's point of view, `%clk` the mover's remaining time and free text.
+// They may also have a `?!`, `?` or `??` mark and one variation in
+// parentheses, e.g. `12. c2_c4?? {Blunder} (12. d1_d4 d8_d5)`. Shop moves
+// go to a fresh shop whose hands replace the one in `Start`; without shop
+// and deploy moves the game starts at `Fight`. `Variant` and `Result` are
+// required, the other tags are optional.
+
+const LINE_WIDTH: usize = 80;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameResult {
+    WhiteWins,
+    BlackWins,
+    Draw,
+    /// The game could not be continued, e.g. a side had no legal drop.
+    Aborted,
+}
+
+impl Display for GameResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let result = match self {
+            Self::WhiteWins => "1-0",
+            Self::BlackWins => "0-1",
+            Self::Draw => "1/2-1/2",
+            Self::Aborted => "*",
+        };
+        write!(f, "{result}")
+    }
+}
+
+impl FromStr for GameResult {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1-0" => Ok(Self::WhiteWins),
+            "0-1" => Ok(Self::BlackWins),
+            "1/2-1/2" => Ok(Self::Draw),
+            "*" => Ok(Self::Aborted),
+            _ => Err(()),
+        }
+    }
+}
+
+impl GameResult {
+    pub(crate) fn winner(color: Color) -> Self {
+        match color {
+            Color::White => Self::WhiteWins,
+            Color::Black => Self::BlackWins,
+            Color::NoColor => Self::Draw,
+        }
+    }
+}
+
+/// Move quality mark, written after the move like in PGN.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Annotation {
+    Inaccuracy,
+    Mistake,
+    Blunder,
+}
+
+impl Display for Annotation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mark = match self {
+            Self::Inaccuracy => "?!",
+            Self::Mistake => "?",
+            Self::Blunder => "??",
+        };
+        write!(f, "{mark}")
+    }
+}
+
+impl FromStr for Annotation {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "?!" => Ok(Self::Inaccuracy),
+            "?" => Ok(Self::Mistake),
+            "??" => Ok(Self::Blunder),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Fight move with the engine's evaluation and the clock, when known.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FightMove {
+    pub mv: String,
+    /// Centipawns from White's point of view.
+    pub eval: Option<i32>,
+    /// Seconds left on the mover's clock.
+    pub clock: Option<u32>,
+    pub annotation: Option<Annotation>,
+    pub comment: Option<String>,
+    /// Line that could have been played instead, starting with its first move.
+    pub variation: Vec<String>,
+}
+
+impl FightMove {
+    pub fn new(mv: String) -> Self {
+        Self {
+            mv,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RecordError {
+    /// Missing or malformed tag or token.
+    Syntax(String),
+    /// Sfen the crate rejects.
+    BadSfen(String),
+    /// Move that can't be played where it stands.
+    IllegalMove(String),
+}
+
+impl Display for RecordError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Syntax(s) => write!(f, "syntax error at {s}"),
+            Self::BadSfen(s) => write!(f, "bad sfen {s}"),
+            Self::IllegalMove(s) => write!(f, "illegal move {s}"),
+        }
+    }
+}
+
+/// Everything needed to replay a game: shop, deploy and fight moves in the
+/// crate's notation (`+Q`, `Q@d1`, `d1_d4`).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GameRecord {
+    pub variant: Variant,
+    pub seed: u64,
+    /// Board with plinths before the first drop.
+    pub start_sfen: String,
+    pub shop_moves: Vec<String>,
+    pub deploy_moves: Vec<String>,
+    /// Deployed position the fight moves start from.
+    pub fight_sfen: String,
+    pub fight_moves: Vec<FightMove>,
+    pub final_sfen: String,
+    pub result: GameResult,
+}
+
+impl GameRecord {
+    /// Game without moves that starts at `sfen`, before or after the drops.
+    pub fn new(variant: Variant, sfen: &str) -> Self {
+        Self {
+            variant,
+            seed: 0,
+            start_sfen: sfen.to_string(),
+            shop_moves: vec![],
+            deploy_moves: vec![],
+            fight_sfen: sfen.to_string(),
+            fight_moves: vec![],
+            final_sfen: String::new(),
+            result: GameResult::Aborted,
+        }
+    }
+
+    /// Appends a deploy or fight move played in the position `sfen`.
+    pub fn push(&mut self, mv: String, sfen: &str) {
+        if mv.contains('@') {
+            self.fight_sfen.clear();
+            self.deploy_moves.push(mv);
+        } else {
+            if self.fight_sfen.is_empty() {
+                self.fight_sfen = sfen.to_string();
+            }
+            self.fight_moves.push(FightMove::new(mv));
+        }
+    }
+
+    /// Plays the record through the crate's rules, filling in `fight_sfen`
+    /// and `final_sfen`. Returns the final position.
+    pub fn replay<S, B, A, P>(&mut self) -> Result<P, RecordError>
+    where
+        S: Square + Hash,
+        B: BitBoard<S>,
+        A: Attacks<S, B>,
+        P: Board<S, B, A> + Sfen<S, B, A> + Placement<S, B, A> + Play<S, B, A> + Rules<S, B, A>,
+    {
+        let mut position = P::new();
+        position.update_variant(self.variant);
+        if self.shop_moves.is_empty() && self.deploy_moves.is_empty() {
+            position
+                .set_sfen(&self.fight_sfen)
+                .map_err(|_| RecordError::BadSfen(self.fight_sfen.clone()))?;
+        } else {
+            let sfen = self.deploy_sfen::<S>()?;
+            position
+                .set_sfen(&sfen)
+                .map_err(|_| RecordError::BadSfen(sfen))?;
+            for mv in &self.deploy_moves {
+                let placed = match Move::<S>::from_sfen(mv) {
+                    Some(Move::Put { to, piece }) => position.place(piece, to).is_some(),
+                    _ => false,
+                };
+                if !placed {
+                    return Err(RecordError::IllegalMove(mv.clone()));
+                }
+            }
+            // Reloading the deployed position caches the fight-phase legal moves.
+            self.fight_sfen = position.generate_sfen();
+            let _ = position.set_sfen(&self.fight_sfen);
+        }
+
+        let last = self.fight_moves.len();
+        for (ply, fight_move) in self.fight_moves.iter().enumerate() {
+            let illegal = || RecordError::IllegalMove(fight_move.mv.clone());
+            let (from, to) = Move::<S>::from_sfen(&fight_move.mv)
+                .and_then(|mv| mv.info())
+                .ok_or_else(illegal)?;
+            let stm = position.side_to_move();
+            let legal = position
+                .legal_moves(stm)
+                .get(&from)
+                .is_some_and(|targets| (*targets & &to).is_any());
+            if !legal {
+                return Err(illegal());
+            }
+            let played = position.make_move(Move::new(from, to));
+            // The crate lets pinned pieces move; the game ends on a draw.
+            if position.in_check(stm) || (played.is_err() && ply + 1 != last) {
+                return Err(illegal());
+            }
+        }
+        self.final_sfen = position.generate_sfen();
+        Ok(position)
+    }
+
+    /// `Start` with the hands bought by the shop moves.
+    fn deploy_sfen<S: Square>(&self) -> Result<String, RecordError> {
+        if self.shop_moves.is_empty() {
+            return Ok(self.start_sfen.clone());
+        }
+        let mut selection = Selection::<S>::default();
+        selection.update_variant(self.variant);
+        for mv in &self.shop_moves {
+            let bought = match Move::<S>::from_sfen(mv) {
+                Some(mv @ Move::Select { .. }) => selection.play(mv).is_some(),
+                _ => false,
+            };
+            if !bought {
+                return Err(RecordError::IllegalMove(mv.clone()));
+            }
+        }
+        let hand = selection.to_sfen(Color::White, true) + &selection.to_sfen(Color::Black, true);
+        let mut fields: Vec<&str> = self.start_sfen.split_whitespace().collect();
+        if fields.len() != 4 {
+            return Err(RecordError::BadSfen(self.start_sfen.clone()));
+        }
+        fields[2] = &hand;
+        Ok(fields.join(" "))
+    }
+
+    /// Every game in `text`.
+    pub fn parse_all(text: &str) -> Result<Vec<Self>, RecordError> {
+        let mut games = vec![];
+        let mut current = String::new();
+        let mut in_moves = false;
+        for line in text.lines() {
+            let tag = line.trim_start().starts_with('[');
+            if tag && in_moves {
+                games.push(current.parse()?);
+                current.clear();
+                in_moves = false;
+            }
+            in_moves |= !tag && !line.trim().is_empty();
+            current.push_str(line);
+            current.push('\n');
+        }
+        if !current.trim().is_empty() {
+            games.push(current.parse()?);
+        }
+        Ok(games)
+    }
+}
+
+impl Display for GameRecord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "[Variant \"{}\"]", self.variant.to_string())?;
+        writeln!(f, "[Seed \"{}\"]", self.seed)?;
+        for (name, sfen) in [
+            ("Start", &self.start_sfen),
+            ("Fight", &self.fight_sfen),
+            ("Final", &self.final_sfen),
+        ] {
+            if !sfen.is_empty() {
+                writeln!(f, "[{name} \"{sfen}\"]")?;
+            }
+        }
+        writeln!(f, "[Result \"{}\"]", self.result)?;
+        writeln!(f)?;
+
+        let mut tokens: Vec<String> = self
+            .shop_moves
+            .iter()
+            .chain(&self.deploy_moves)
+            .cloned()
+            .collect();
+        let black_starts = self.fight_sfen.split_whitespace().nth(1) == Some("b");
+        for (ply, fight_move) in self.fight_moves.iter().enumerate() {
+            let ply = ply + black_starts as usize;
+            if ply.is_multiple_of(2) {
+                tokens.push(format!("{}.", ply / 2 + 1));
+            } else if ply == 1 {
+                tokens.push(String::from("1..."));
+            }
+            match fight_move.annotation {
+                Some(annotation) => tokens.push(format!("{}{annotation}", fight_move.mv)),
+                None => tokens.push(fight_move.mv.clone()),
+            }
+            let mut comment = vec![];
+            if let Some(eval) = fight_move.eval {
+                comment.push(format!("[%eval {:.2}]", eval as f64 / 100.0));
+            }
+            if let Some(clock) = fight_move.clock {
+                let (h, m, s) = (clock / 3600, clock / 60 % 60, clock % 60);
+                comment.push(format!("[%clk {h}:{m:02}:{s:02}]"));
+            }
+            comment.extend(fight_move.comment.clone());
+            if !comment.is_empty() {
+                tokens.push(format!("{{{}}}", comment.join(" ")));
+            }
+            if let Some((first, rest)) = fight_move.variation.split_first() {
+                let number = match ply.is_multiple_of(2) {
+                    true => format!("{}.", ply / 2 + 1),
+                    false => format!("{}...", ply / 2 + 1),
+                };
+                tokens.push(format!("({number}"));
+                tokens.push(first.clone());
+                tokens.extend(rest.iter().cloned());
+                if let Some(last) = tokens.last_mut() {
+                    last.push(')');
+                }
+            }
+        }
+        tokens.push(self.result.to_string());
+
+        let mut line = String::new();
+        for token in tokens {
+            if !line.is_empty() && line.len() + 1 + token.len() > LINE_WIDTH {
+                writeln!(f, "{line}")?;
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&token);
+        }
+        writeln!(f, "{line}")
+    }
+}
+
+/// Parses one game. Moves are only checked for their shape, `replay`
+/// validates them.
+impl FromStr for GameRecord {
+    type Err = RecordError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut tags = vec![];
+        let mut movetext = String::new();
+        for line in s.lines().map(str::trim) {
+            if let Some(tag) = line.strip_prefix('[') {
+                let syntax = || RecordError::Syntax(line.to_string());
+                let tag = tag.strip_suffix(']').ok_or_else(syntax)?;
+                let (name, value) = tag.split_once(' ').ok_or_else(syntax)?;
+                let value = value.trim().trim_matches('"');
+                tags.push((name.to_string(), value.to_string()));
+            } else {
+                movetext.push_str(line);
+                movetext.push(' ');
+            }
+        }
+        let tag = |name: &str| {
+            tags.iter()
+                .find(|(tag, _)| tag == name)
+                .map(|(_, value)| value.clone())
+        };
+
+        let variant = tag("Variant")
+            .and_then(|name| VARIANTS.into_iter().find(|v| v.to_string() == name))
+            .ok_or_else(|| RecordError::Syntax(String::from("Variant tag")))?;
+        let result = tag("Result")
+            .and_then(|result| result.parse().ok())
+            .ok_or_else(|| RecordError::Syntax(String::from("Result tag")))?;
+        let mut record = Self {
+            variant,
+            seed: tag("Seed").and_then(|s| s.parse().ok()).unwrap_or(0),
+            start_sfen: tag("Start").unwrap_or_default(),
+            shop_moves: vec![],
+            deploy_moves: vec![],
+            fight_sfen: tag("Fight").unwrap_or_default(),
+            fight_moves: vec![],
+            final_sfen: tag("Final").unwrap_or_default(),
+            result,
+        };
+
+        let mut rest = movetext.as_str();
+        loop {
+            rest = rest.trim_start();
+            if rest.is_empty() {
+                break;
+            }
+            if let Some(comment) = rest.strip_prefix('{') {
+                let (comment, after) = comment
+                    .split_once('}')
+                    .ok_or_else(|| RecordError::Syntax(rest.to_string()))?;
+                let fight_move = record
+                    .fight_moves
+                    .last_mut()
+                    .ok_or_else(|| RecordError::Syntax(comment.to_string()))?;
+                fight_move.eval = parse_eval(comment).or(fight_move.eval);
+                fight_move.clock = parse_clock(comment).or(fight_move.clock);
+                let text = strip_commands(comment);
+                if !text.is_empty() {
+                    fight_move.comment = Some(text);
+                }
+                rest = after;
+                continue;
+            }
+            if let Some(variation) = rest.strip_prefix('(') {
+                let (variation, after) = variation
+                    .split_once(')')
+                    .ok_or_else(|| RecordError::Syntax(rest.to_string()))?;
+                let fight_move = record
+                    .fight_moves
+                    .last_mut()
+                    .ok_or_else(|| RecordError::Syntax(variation.to_string()))?;
+                fight_move.variation = variation
+                    .split_whitespace()
+                    .filter(|token| !is_move_number(token))
+                    .map(String::from)
+                    .collect();
+                rest = after;
+                continue;
+            }
+            let end = rest.find([' ', '{', '(']).unwrap_or(rest.len());
+            let token = &rest[..end];
+            rest = &rest[end..];
+            if token.parse::<GameResult>().is_ok() || is_move_number(token) {
+                continue;
+            }
+            if token.starts_with('+') {
+                record.shop_moves.push(token.to_string());
+            } else if token.contains('@') {
+                record.deploy_moves.push(token.to_string());
+            } else if token.contains('_') {
+                let mv = token.trim_end_matches(['?', '!']);
+                record.fight_moves.push(FightMove {
+                    annotation: token[mv.len()..].parse().ok(),
+                    ..FightMove::new(mv.to_string())
+                });
+            } else {
+                return Err(RecordError::Syntax(token.to_string()));
+            }
+        }
+        Ok(record)
+    }
+}
+
+pub(crate) const VARIANTS: [Variant; 6] = [
+    Variant::Shuuro,
+    Variant::ShuuroFairy,
+    Variant::Standard,
+    Variant::StandardFairy,
+    Variant::ShuuroMini,
+    Variant::ShuuroMiniFairy,
+];
+
+fn is_move_number(token: &str) -> bool {
+    let digits = token.trim_end_matches('.');
+    digits.len() < token.len() && !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Value after `[%name ` in a comment.
+fn command<'a>(comment: &'a str, name: &str) -> Option<&'a str> {
+    let start = comment.find(&format!("[%{name} "))? + name.len() + 3;
+    let value = &comment[start..];
+    value.split(']').next().map(str::trim)
+}
+
+/// Comment text without its `[%...]` commands.
+fn strip_commands(comment: &str) -> String {
+    let mut text = String::new();
+    let mut rest = comment;
+    while let Some(start) = rest.find("[%") {
+        text.push_str(&rest[..start]);
+        rest = rest[start..].split_once(']').map_or("", |(_, after)| after);
+    }
+    text.push_str(rest);
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn parse_eval(comment: &str) -> Option<i32> {
+    let pawns: f64 = command(comment, "eval")?.parse().ok()?;
+    Some((pawns * 100.0).round() as i32)
+}
+
+fn parse_clock(comment: &str) -> Option<u32> {
+    command(comment, "clk")?
+        .split(':')
+        .try_fold(0, |total, part| {
+            Some(total * 60 + part.parse::<u32>().ok()?)
+        })
+}
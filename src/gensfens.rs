@@ -0,0 +1,371 @@
+use shuuro::{
+    Color, Move, Square,
+    attacks::Attacks,
+    bitboard::BitBoard,
+    position::{Board, Outcome, Placement, Play, Rules, Sfen},
+};
+use std::{
+    collections::HashSet,
+    fmt::Display,
+    fs::File,
+    hash::Hash,
+    io::{self, BufWriter, Write},
+    str::FromStr,
+    sync::Arc,
+    thread,
+};
+
+use crate::{
+    engine::{Engine, EngineDefs, STACK_SIZE, SearchLimits},
+    move_list::MoveList,
+    nnue::{Network, Nnue},
+    prng::Prng,
+    zobrist,
+};
+
+// Records are written in game order, so the output only depends on the
+// options and not on the number of threads.
+//
+// Text format, one record per line:
+//
+//   <sfen>;<score>;<result>;<ply>
+//
+// Binary format, little-endian:
+//
+//   sfen_len u16, sfen [sfen_len] utf-8, score i16, result i8, ply u16
+//
+// `score` and `result` (1 win, 0 draw, -1 loss) are from the side to move's
+// point of view.
+
+const MATE_SCORE: i32 = 32000;
+const BATCH_GAMES: u64 = 64;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RecordFormat {
+    Text,
+    Binary,
+}
+
+#[derive(Clone, Debug)]
+pub struct GensfensOptions {
+    pub limits: SearchLimits,
+    pub games: u64,
+    pub threads: usize,
+    pub seed: u64,
+    pub random_plies: u16,
+    pub max_ply: u16,
+    pub eval_limit: i32,
+    pub output: String,
+    pub format: RecordFormat,
+    pub book: Option<String>,
+}
+
+impl Default for GensfensOptions {
+    fn default() -> Self {
+        Self {
+            limits: SearchLimits {
+                depth: Some(2),
+                ..Default::default()
+            },
+            games: 100,
+            threads: 1,
+            seed: 0,
+            random_plies: 8,
+            max_ply: 300,
+            eval_limit: 3000,
+            output: String::from("gensfens.txt"),
+            format: RecordFormat::Text,
+            book: None,
+        }
+    }
+}
+
+impl GensfensOptions {
+    /// Parses `depth <n> nodes <n> games <n> threads <n> seed <n> random <n>
+    /// maxply <n> evallimit <cp> output <file> format <text|bin> book <file>`.
+    pub fn parse(args: &str) -> Self {
+        let mut options = Self::default();
+        let mut tokens = args.split_whitespace();
+        while let Some(token) = tokens.next() {
+            let Some(value) = tokens.next() else {
+                break;
+            };
+            match token {
+                "depth" => {
+                    options.limits.depth = value.parse().ok();
+                }
+                "nodes" => {
+                    options.limits.nodes = value.parse().ok();
+                    options.limits.depth = None;
+                }
+                "games" => options.games = value.parse().unwrap_or(options.games),
+                "threads" => options.threads = value.parse().unwrap_or(1).max(1),
+                "seed" => options.seed = value.parse().unwrap_or(0),
+                "random" => options.random_plies = value.parse().unwrap_or(0),
+                "maxply" => options.max_ply = value.parse().unwrap_or(options.max_ply),
+                "evallimit" => options.eval_limit = value.parse().unwrap_or(options.eval_limit),
+                "output" => options.output = value.to_string(),
+                "format" => {
+                    options.format = if value == "bin" {
+                        RecordFormat::Binary
+                    } else {
+                        RecordFormat::Text
+                    }
+                }
+                "book" => options.book = Some(value.to_string()),
+                _ => (),
+            }
+        }
+        options
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Record {
+    pub key: u64,
+    pub sfen: String,
+    pub score: i32,
+    pub result: i8,
+    pub ply: u16,
+}
+
+/// Parses a text record. Keys are not written, so `key` is left at 0.
+impl FromStr for Record {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut fields = s.trim().split(';');
+        let mut next = || fields.next().ok_or(());
+        let sfen = next()?.to_string();
+        let score = next()?.parse().map_err(|_| ())?;
+        let result = next()?.parse().map_err(|_| ())?;
+        let ply = next()?.parse().map_err(|_| ())?;
+        Ok(Self {
+            key: 0,
+            sfen,
+            score,
+            result,
+            ply,
+        })
+    }
+}
+
+impl Record {
+    pub fn write<W: Write>(&self, out: &mut W, format: RecordFormat) -> io::Result<()> {
+        match format {
+            RecordFormat::Text => writeln!(
+                out,
+                "{};{};{};{}",
+                self.sfen, self.score, self.result, self.ply
+            ),
+            RecordFormat::Binary => {
+                out.write_all(&(self.sfen.len() as u16).to_le_bytes())?;
+                out.write_all(self.sfen.as_bytes())?;
+                let score = self.score.clamp(i16::MIN as i32, i16::MAX as i32) as i16;
+                out.write_all(&score.to_le_bytes())?;
+                out.write_all(&self.result.to_le_bytes())?;
+                out.write_all(&self.ply.to_le_bytes())
+            }
+        }
+    }
+}
+
+/// Plays `options.games` self-play games and writes quiet positions with
+/// their search score and the final game result. Returns the number of
+/// written records.
+pub fn run<E, S, B, A, P, D, const FILE: usize, const BITBOARD_SIZE: usize, const RANK: usize>(
+    options: &GensfensOptions,
+    network: Option<Arc<Network>>,
+) -> io::Result<u64>
+where
+    E: Engine<S, B, A, P, D, FILE, BITBOARD_SIZE, RANK>,
+    S: Square + Hash + Send + 'static,
+    B: BitBoard<S>,
+    A: Attacks<S, B>,
+    P: Sized
+        + Display
+        + Clone
+        + Board<S, B, A>
+        + Sfen<S, B, A>
+        + Placement<S, B, A>
+        + Play<S, B, A>
+        + Rules<S, B, A>
+        + Send
+        + 'static,
+    D: EngineDefs<S, B, FILE>,
+{
+    E::init();
+    let starts: Vec<String> = match &options.book {
+        Some(book) => std::fs::read_to_string(book)?
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+            .map(String::from)
+            .collect(),
+        None => D::start_positions().iter().map(|s| s.to_string()).collect(),
+    };
+    if starts.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "no start positions",
+        ));
+    }
+
+    let mut out = BufWriter::new(File::create(&options.output)?);
+    let mut seen = HashSet::new();
+    let mut written = 0;
+    let mut first = 0;
+    while first < options.games {
+        let last = (first + BATCH_GAMES * options.threads as u64).min(options.games);
+        let mut games: Vec<(u64, Vec<Record>)> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..options.threads as u64)
+                .map(|t| {
+                    let network = network.clone();
+                    let starts = &starts;
+                    thread::Builder::new()
+                        .stack_size(STACK_SIZE)
+                        .spawn_scoped(scope, move || {
+                            let mut engine = E::new();
+                            *engine.nnue_mut() = network.map(Nnue::new);
+                            (first + t..last)
+                                .step_by(options.threads)
+                                .map(|game| (game, play_game(&mut engine, options, starts, game)))
+                                .collect::<Vec<_>>()
+                        })
+                        .expect("failed to spawn gensfens thread")
+                })
+                .collect();
+            handles
+                .into_iter()
+                .flat_map(|h| h.join().expect("gensfens thread panicked"))
+                .collect()
+        });
+        games.sort_by_key(|(game, _)| *game);
+        for record in games.into_iter().flat_map(|(_, records)| records) {
+            if seen.insert(record.key) {
+                record.write(&mut out, options.format)?;
+                written += 1;
+            }
+        }
+        println!(
+            "info string gensfens {last}/{} games, {written} positions",
+            options.games
+        );
+        first = last;
+    }
+    out.flush()?;
+    Ok(written)
+}
+
+fn play_game<E, S, B, A, P, D, const FILE: usize, const BITBOARD_SIZE: usize, const RANK: usize>(
+    engine: &mut E,
+    options: &GensfensOptions,
+    starts: &[String],
+    game: u64,
+) -> Vec<Record>
+where
+    E: Engine<S, B, A, P, D, FILE, BITBOARD_SIZE, RANK>,
+    S: Square + Hash + Send + 'static,
+    B: BitBoard<S>,
+    A: Attacks<S, B>,
+    P: Sized
+        + Display
+        + Clone
+        + Board<S, B, A>
+        + Sfen<S, B, A>
+        + Placement<S, B, A>
+        + Play<S, B, A>
+        + Rules<S, B, A>
+        + Send
+        + 'static,
+    D: EngineDefs<S, B, FILE>,
+{
+    let mut prng = Prng::stream(options.seed, game);
+    let mut position = P::new();
+    if position
+        .set_sfen(&starts[prng.below(starts.len())])
+        .is_err()
+    {
+        return vec![];
+    }
+
+    // Pending records with the side to move of each one.
+    let mut records: Vec<(Record, Color)> = vec![];
+    let mut winner = None;
+    for ply in 0..options.max_ply {
+        let stm = position.side_to_move();
+        let moves: Vec<Move<S>> = MoveList::legal(&position, stm).into_iter().collect();
+        if moves.is_empty() {
+            if position.in_check(stm) {
+                winner = Some(stm.flip());
+            }
+            break;
+        }
+
+        let mv = if ply < options.random_plies {
+            moves[prng.below(moves.len())].clone()
+        } else {
+            let result = engine.search(&position, options.limits.clone());
+            let Some(best_move) = result.best_move else {
+                break;
+            };
+            let score = result.score.clamp(-MATE_SCORE, MATE_SCORE);
+            let score = if stm == Color::White { score } else { -score };
+            if is_quiet(&position, stm) {
+                let record = Record {
+                    key: zobrist::hash(&position),
+                    sfen: position.generate_sfen(),
+                    score,
+                    result: 0,
+                    ply: position.ply(),
+                };
+                records.push((record, stm));
+            }
+            if score.abs() >= options.eval_limit {
+                winner = Some(if score > 0 { stm } else { stm.flip() });
+                break;
+            }
+            best_move
+        };
+
+        match position.make_move(mv) {
+            Ok(Outcome::Checkmate { color }) => {
+                winner = Some(color);
+                break;
+            }
+            Ok(_) => (),
+            // Repetition, stalemate and insufficient material are draws.
+            Err(_) => break,
+        }
+    }
+
+    records
+        .into_iter()
+        .map(|(mut record, stm)| {
+            record.result = match winner {
+                Some(color) if color == stm => 1,
+                Some(_) => -1,
+                None => 0,
+            };
+            record
+        })
+        .collect()
+}
+
+/// Positions in check or with a capture available make bad training data.
+fn is_quiet<S, B, A, P>(position: &P, stm: Color) -> bool
+where
+    S: Square + Hash,
+    B: BitBoard<S>,
+    A: Attacks<S, B>,
+    P: Board<S, B, A> + Play<S, B, A> + Sfen<S, B, A> + Placement<S, B, A> + Rules<S, B, A>,
+{
+    if position.in_check(stm) {
+        return false;
+    }
+    let enemies = position.player_bb(stm.flip());
+    !position
+        .legal_moves(stm)
+        .values()
+        .any(|targets| (*targets & &enemies).is_any())
+}
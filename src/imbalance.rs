@@ -0,0 +1,228 @@
+use std::{f64::consts::LN_10, fmt, fs, io, path::Path, str::FromStr};
+
+/// Scales centipawns to a win probability, as in Texel tuning. Results
+/// of fast self-play games follow the evaluation much more loosely, so
+/// the scale is fitted to the samples between 0 and twice this.
+const TEXEL_K: f64 = LN_10 / 400.0;
+
+/// Pulls coefficients that little data speaks for back to the start table.
+const REGULARISATION: f64 = 1e-7;
+
+/// Adam step size in centipawns.
+const LEARNING_RATE: f64 = 0.5;
+
+/// Training position for `ImbalanceTable::tune`: White and Black piece
+/// counts, the evaluation without the imbalance term and the result, all
+/// from White's point of view. `result` is 1, 0.5 or 0.
+#[derive(Clone, Copy, Debug)]
+pub struct Sample {
+    pub counts: [[u32; 9]; 2],
+    pub eval: i32,
+    pub result: f64,
+}
+
+/// Second-order material term. For every pair of piece types `i >= j`,
+/// `ours[i][j]` is added per own `i` and own `j`, and `theirs[i][j]` per own
+/// `i` and enemy `j`. The king is always there once, so column 0 acts as a
+/// linear adjustment. Indexed by `PieceType::index`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ImbalanceTable {
+    pub ours: [[i32; 9]; 9],
+    pub theirs: [[i32; 9]; 9],
+}
+
+impl ImbalanceTable {
+    /// Imbalance from White's point of view for `[white, black]` piece counts.
+    pub fn evaluate(&self, piece_counts: &[[u32; 9]; 2]) -> i32 {
+        self.side(&piece_counts[0], &piece_counts[1])
+            - self.side(&piece_counts[1], &piece_counts[0])
+    }
+
+    fn side(&self, us: &[u32; 9], them: &[u32; 9]) -> i32 {
+        let mut bonus = 0;
+        for i in 0..9 {
+            if us[i] == 0 {
+                continue;
+            }
+            let mut v = 0;
+            for j in 0..=i {
+                v += self.ours[i][j] * us[j] as i32 + self.theirs[i][j] * them[j] as i32;
+            }
+            bonus += us[i] as i32 * v;
+        }
+        bonus
+    }
+
+    /// Mean squared error between results and win probabilities predicted
+    /// with `scale` per centipawn.
+    pub fn error(&self, samples: &[Sample], scale: f64) -> f64 {
+        let sum: f64 = samples
+            .iter()
+            .map(|sample| {
+                let eval = sample.eval + self.evaluate(&sample.counts);
+                (sample.result - win_probability(eval as f64, scale)).powi(2)
+            })
+            .sum();
+        sum / samples.len().max(1) as f64
+    }
+
+    /// Scale with the lowest `error`, by ternary search.
+    pub fn fit_scale(&self, samples: &[Sample]) -> f64 {
+        let (mut low, mut high) = (0.0, 2.0 * TEXEL_K);
+        for _ in 0..60 {
+            let a = low + (high - low) / 3.0;
+            let b = high - (high - low) / 3.0;
+            if self.error(samples, a) < self.error(samples, b) {
+                high = b;
+            } else {
+                low = a;
+            }
+        }
+        (low + high) / 2.0
+    }
+
+    /// Texel tuning with Adam, starting from and regularised towards `self`.
+    /// Only pairs of non-king types are tuned: the king column is a linear
+    /// term that belongs to the piece values, and `theirs[i][i]` cancels.
+    pub fn tune(&self, samples: &[Sample], scale: f64, iterations: usize) -> Self {
+        let mut params = vec![];
+        for i in 1..9 {
+            for j in 1..=i {
+                params.push((false, i, j));
+                if j < i {
+                    params.push((true, i, j));
+                }
+            }
+        }
+        let start: Vec<f64> = params
+            .iter()
+            .map(|&(theirs, i, j)| self.get(theirs, i, j) as f64)
+            .collect();
+
+        // The evaluation is linear in the coefficients.
+        let features: Vec<Vec<f64>> = samples
+            .iter()
+            .map(|sample| {
+                let [w, b] = sample.counts.map(|side| side.map(|count| count as f64));
+                params
+                    .iter()
+                    .map(|&(theirs, i, j)| match theirs {
+                        false => w[i] * w[j] - b[i] * b[j],
+                        true => w[i] * b[j] - b[i] * w[j],
+                    })
+                    .collect()
+            })
+            .collect();
+        let base: Vec<f64> = samples
+            .iter()
+            .zip(&features)
+            .map(|(sample, features)| {
+                let tuned: f64 = features.iter().zip(&start).map(|(f, v)| f * v).sum();
+                (sample.eval + self.evaluate(&sample.counts)) as f64 - tuned
+            })
+            .collect();
+
+        let mut values = start.clone();
+        let (mut m, mut v) = (vec![0.0; params.len()], vec![0.0; params.len()]);
+        let n = samples.len().max(1) as f64;
+        for step in 1..=iterations {
+            let mut gradient: Vec<f64> = values
+                .iter()
+                .zip(&start)
+                .map(|(value, start)| 2.0 * REGULARISATION * (value - start))
+                .collect();
+            for ((sample, features), base) in samples.iter().zip(&features).zip(&base) {
+                let eval = base
+                    + features
+                        .iter()
+                        .zip(&values)
+                        .map(|(f, v)| f * v)
+                        .sum::<f64>();
+                let p = win_probability(eval, scale);
+                let step = -2.0 * (sample.result - p) * p * (1.0 - p) * scale / n;
+                for (g, f) in gradient.iter_mut().zip(features) {
+                    *g += step * f;
+                }
+            }
+            for (k, g) in gradient.iter().enumerate() {
+                m[k] = 0.9 * m[k] + 0.1 * g;
+                v[k] = 0.999 * v[k] + 0.001 * g * g;
+                let m_hat = m[k] / (1.0 - 0.9f64.powi(step as i32));
+                let v_hat = v[k] / (1.0 - 0.999f64.powi(step as i32));
+                values[k] -= LEARNING_RATE * m_hat / (v_hat.sqrt() + 1e-12);
+            }
+        }
+
+        let mut table = *self;
+        for (&(theirs, i, j), value) in params.iter().zip(&values) {
+            let row = if theirs {
+                &mut table.theirs
+            } else {
+                &mut table.ours
+            };
+            row[i][j] = value.round() as i32;
+        }
+        table
+    }
+
+    fn get(&self, theirs: bool, i: usize, j: usize) -> i32 {
+        if theirs {
+            self.theirs[i][j]
+        } else {
+            self.ours[i][j]
+        }
+    }
+
+    pub fn load<T: AsRef<Path>>(path: T) -> io::Result<Self> {
+        fs::read_to_string(path)?
+            .parse()
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "bad imbalance table"))
+    }
+}
+
+fn win_probability(eval: f64, scale: f64) -> f64 {
+    1.0 / (1.0 + (-scale * eval).exp())
+}
+
+/// Text format: the 9 rows of `ours` followed by the 9 rows of `theirs`,
+/// nine numbers each. Blank lines and `#` comments are skipped.
+impl FromStr for ImbalanceTable {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut rows = s
+            .lines()
+            .map(|line| line.split('#').next().unwrap_or_default().trim())
+            .filter(|line| !line.is_empty());
+        let mut table = Self {
+            ours: [[0; 9]; 9],
+            theirs: [[0; 9]; 9],
+        };
+        for row in table.ours.iter_mut().chain(table.theirs.iter_mut()) {
+            let values: Vec<i32> = rows
+                .next()
+                .ok_or(())?
+                .split_whitespace()
+                .map(|v| v.parse().map_err(|_| ()))
+                .collect::<Result<_, _>>()?;
+            *row = values.try_into().map_err(|_| ())?;
+        }
+        if rows.next().is_some() {
+            return Err(());
+        }
+        Ok(table)
+    }
+}
+
+impl fmt::Display for ImbalanceTable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (name, table) in [("ours", &self.ours), ("theirs", &self.theirs)] {
+            writeln!(f, "# {name}: K Q R B N P C A G")?;
+            for row in table {
+                let row: Vec<String> = row.iter().map(|v| v.to_string()).collect();
+                writeln!(f, "{}", row.join(" "))?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,6 +1,26 @@
+pub mod annotate;
+pub mod army;
+pub mod autoplay;
+pub mod bench;
+pub mod book;
+pub mod endgame;
 pub mod engine;
 pub mod engine12;
 pub mod engine6;
 pub mod engine8;
+pub mod game_record;
+pub mod gensfens;
+pub mod imbalance;
+pub mod match_runner;
+pub mod move_list;
+pub mod nnue;
+pub mod pawn_table;
+pub mod personality;
+pub mod prng;
+pub mod shop;
+pub mod skill;
+pub mod tablebase;
+pub mod testsuite;
+pub mod zobrist;
 
 pub use engine::{Engine, EngineDefs, GamePhase};
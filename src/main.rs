@@ -1,10 +1,30 @@
 use engine::Engine;
 use engine8::search::Engine8;
 
+pub mod annotate;
+pub mod army;
+pub mod autoplay;
+pub mod bench;
+pub mod book;
+pub mod endgame;
 pub mod engine;
 pub mod engine12;
 pub mod engine6;
 pub mod engine8;
+pub mod game_record;
+pub mod gensfens;
+pub mod imbalance;
+pub mod match_runner;
+pub mod move_list;
+pub mod nnue;
+pub mod pawn_table;
+pub mod personality;
+pub mod prng;
+pub mod shop;
+pub mod skill;
+pub mod tablebase;
+pub mod testsuite;
+pub mod zobrist;
 
 // fn main() {
 //     let mut pos = P8::default();
@@ -20,6 +40,17 @@ fn main() {
     // dbg!(best_value);
     // dbg!(best_value.max(250));
 
+    // `shuuro-engine bench [depth]` prints the bench and exits.
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().is_some_and(|arg| arg == "bench") {
+        let depth = args
+            .get(1)
+            .and_then(|depth| depth.parse().ok())
+            .unwrap_or(bench::DEFAULT_DEPTH);
+        println!("bench {}", bench::run_all(depth));
+        return;
+    }
+
     let mut engine = Engine8::new();
     engine.uci_loop("4k3/4r3/8/8/6n1/4B1/5PPP/5BNK b - 1");
     // engine.uci_loop("10k1/12/10n1/12/12/12/12/12/10n1/12/9_._.P/8BBNK b - 1");
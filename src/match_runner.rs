@@ -0,0 +1,395 @@
+use shuuro::{
+    Square,
+    attacks::Attacks,
+    bitboard::BitBoard,
+    position::{Board, Placement, Play, Rules, Sfen},
+};
+use std::{fmt, fmt::Display, hash::Hash, io, thread};
+
+use crate::{
+    autoplay::{GameResult, MAX_FIGHT_PLIES, fight},
+    engine::{Engine, EngineDefs, STACK_SIZE, SearchLimits},
+};
+
+const BATCH_GAMES: u64 = 16;
+
+/// Engine setup as `Name=Value` pairs, applied with `set_option`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EngineConfig {
+    pub options: Vec<(String, String)>,
+}
+
+impl EngineConfig {
+    /// Parses `Name=Value,Name=Value`; `-` is the default engine.
+    pub fn parse(s: &str) -> Self {
+        let options = s
+            .split(',')
+            .filter_map(|option| option.split_once('='))
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        Self { options }
+    }
+
+    fn build<E, S, B, A, P, D, const FILE: usize, const BITBOARD_SIZE: usize, const RANK: usize>(
+        &self,
+    ) -> E
+    where
+        E: Engine<S, B, A, P, D, FILE, BITBOARD_SIZE, RANK>,
+        S: Square + Hash + Send + 'static,
+        B: BitBoard<S>,
+        A: Attacks<S, B>,
+        P: Sized
+            + Display
+            + Clone
+            + Board<S, B, A>
+            + Sfen<S, B, A>
+            + Placement<S, B, A>
+            + Play<S, B, A>
+            + Rules<S, B, A>
+            + Send
+            + 'static,
+        D: EngineDefs<S, B, FILE>,
+    {
+        let mut engine = E::new();
+        for (name, value) in &self.options {
+            engine.set_option(name, value);
+        }
+        engine
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct MatchOptions {
+    pub first: EngineConfig,
+    pub second: EngineConfig,
+    pub limits: SearchLimits,
+    pub games: u64,
+    pub threads: usize,
+    pub book: Option<String>,
+    pub sprt: Sprt,
+}
+
+impl Default for MatchOptions {
+    fn default() -> Self {
+        Self {
+            first: EngineConfig::default(),
+            second: EngineConfig::default(),
+            limits: SearchLimits {
+                depth: Some(2),
+                ..Default::default()
+            },
+            games: 100,
+            threads: 1,
+            book: None,
+            sprt: Sprt::default(),
+        }
+    }
+}
+
+impl MatchOptions {
+    /// Parses `first <config> second <config> games <n> threads <n>
+    /// depth <n> nodes <n> book <file> elo0 <elo> elo1 <elo> alpha <p>
+    /// beta <p>`.
+    pub fn parse(args: &str) -> Self {
+        let mut options = Self::default();
+        let mut tokens = args.split_whitespace();
+        while let Some(token) = tokens.next() {
+            let Some(value) = tokens.next() else {
+                break;
+            };
+            match token {
+                "first" => options.first = EngineConfig::parse(value),
+                "second" => options.second = EngineConfig::parse(value),
+                "games" => options.games = value.parse().unwrap_or(options.games),
+                "threads" => options.threads = value.parse().unwrap_or(1).max(1),
+                "depth" => {
+                    options.limits.depth = value.parse().ok();
+                }
+                "nodes" => {
+                    options.limits.nodes = value.parse().ok();
+                    options.limits.depth = None;
+                }
+                "book" => options.book = Some(value.to_string()),
+                "elo0" => options.sprt.elo0 = value.parse().unwrap_or(options.sprt.elo0),
+                "elo1" => options.sprt.elo1 = value.parse().unwrap_or(options.sprt.elo1),
+                "alpha" => options.sprt.alpha = value.parse().unwrap_or(options.sprt.alpha),
+                "beta" => options.sprt.beta = value.parse().unwrap_or(options.sprt.beta),
+                _ => (),
+            }
+        }
+        options
+    }
+}
+
+/// Sequential probability ratio test of `elo1` against `elo0`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sprt {
+    pub elo0: f64,
+    pub elo1: f64,
+    pub alpha: f64,
+    pub beta: f64,
+}
+
+impl Default for Sprt {
+    fn default() -> Self {
+        Self {
+            elo0: 0.0,
+            elo1: 5.0,
+            alpha: 0.05,
+            beta: 0.05,
+        }
+    }
+}
+
+impl Sprt {
+    /// Log-likelihood ratio bounds, H0 below the first and H1 above the second.
+    pub fn bounds(&self) -> (f64, f64) {
+        (
+            (self.beta / (1.0 - self.alpha)).ln(),
+            ((1.0 - self.beta) / self.alpha).ln(),
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    /// The first engine is at least `elo1` stronger.
+    H1,
+    /// The first engine is at most `elo0` stronger.
+    H0,
+    Continue,
+}
+
+impl Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let verdict = match self {
+            Self::H1 => "H1 accepted",
+            Self::H0 => "H0 accepted",
+            Self::Continue => "continue",
+        };
+        write!(f, "{verdict}")
+    }
+}
+
+/// Results from the first engine's point of view.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MatchStats {
+    pub wins: u64,
+    pub draws: u64,
+    pub losses: u64,
+}
+
+impl MatchStats {
+    pub fn games(&self) -> u64 {
+        self.wins + self.draws + self.losses
+    }
+
+    pub fn add(&mut self, result: GameResult, first_is_white: bool) {
+        match (result, first_is_white) {
+            (GameResult::WhiteWins, true) | (GameResult::BlackWins, false) => self.wins += 1,
+            (GameResult::WhiteWins, false) | (GameResult::BlackWins, true) => self.losses += 1,
+            (GameResult::Draw, _) => self.draws += 1,
+            (GameResult::Aborted, _) => (),
+        }
+    }
+
+    /// Mean score and its per-game variance.
+    fn score(&self) -> (f64, f64) {
+        let n = self.games() as f64;
+        let (w, d, l) = (
+            self.wins as f64 / n,
+            self.draws as f64 / n,
+            self.losses as f64 / n,
+        );
+        let score = w + d / 2.0;
+        let variance = w * (1.0 - score).powi(2) + d * (0.5 - score).powi(2) + l * score.powi(2);
+        (score, variance)
+    }
+
+    /// Elo difference with its 95% error margin.
+    pub fn elo(&self) -> (f64, f64) {
+        if self.games() == 0 {
+            return (0.0, 0.0);
+        }
+        let (score, variance) = self.score();
+        let margin = 1.96 * (variance / self.games() as f64).sqrt();
+        let elo = score_to_elo(score);
+        let high = score_to_elo(score + margin);
+        let low = score_to_elo(score - margin);
+        (elo, (high - low) / 2.0)
+    }
+
+    /// Log-likelihood ratio of H1 over H0, normal approximation of the
+    /// trinomial model.
+    pub fn llr(&self, sprt: &Sprt) -> f64 {
+        if self.games() == 0 {
+            return 0.0;
+        }
+        let (score, variance) = self.score();
+        // All results identical, nothing to scale the score by.
+        if variance == 0.0 {
+            return 0.0;
+        }
+        let (s0, s1) = (elo_to_score(sprt.elo0), elo_to_score(sprt.elo1));
+        self.games() as f64 * (s1 - s0) * (2.0 * score - s0 - s1) / (2.0 * variance)
+    }
+
+    pub fn verdict(&self, sprt: &Sprt) -> Verdict {
+        let llr = self.llr(sprt);
+        let (lower, upper) = sprt.bounds();
+        if llr >= upper {
+            Verdict::H1
+        } else if llr <= lower {
+            Verdict::H0
+        } else {
+            Verdict::Continue
+        }
+    }
+}
+
+impl Display for MatchStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (elo, margin) = self.elo();
+        write!(
+            f,
+            "+{} ={} -{} elo {elo:.1} +/- {margin:.1}",
+            self.wins, self.draws, self.losses
+        )
+    }
+}
+
+fn elo_to_score(elo: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-elo / 400.0))
+}
+
+fn score_to_elo(score: f64) -> f64 {
+    let score = score.clamp(1e-6, 1.0 - 1e-6);
+    -400.0 * (1.0 / score - 1.0).log10()
+}
+
+/// Plays `first` against `second` from the start positions, each position
+/// twice with colours swapped. Stops early once the SPRT is decided.
+pub fn run<E, S, B, A, P, D, const FILE: usize, const BITBOARD_SIZE: usize, const RANK: usize>(
+    options: &MatchOptions,
+) -> io::Result<(MatchStats, Verdict)>
+where
+    E: Engine<S, B, A, P, D, FILE, BITBOARD_SIZE, RANK>,
+    S: Square + Hash + Send + 'static,
+    B: BitBoard<S>,
+    A: Attacks<S, B>,
+    P: Sized
+        + Display
+        + Clone
+        + Board<S, B, A>
+        + Sfen<S, B, A>
+        + Placement<S, B, A>
+        + Play<S, B, A>
+        + Rules<S, B, A>
+        + Send
+        + 'static,
+    D: EngineDefs<S, B, FILE>,
+{
+    E::init();
+    let starts: Vec<String> = match &options.book {
+        Some(book) => std::fs::read_to_string(book)?
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+            .map(String::from)
+            .collect(),
+        None => D::start_positions().iter().map(|s| s.to_string()).collect(),
+    };
+    if starts.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "no start positions",
+        ));
+    }
+
+    let mut stats = MatchStats::default();
+    let mut verdict = Verdict::Continue;
+    let mut first = 0;
+    while first < options.games && verdict == Verdict::Continue {
+        let last = (first + BATCH_GAMES * options.threads as u64).min(options.games);
+        let results: Vec<(u64, GameResult)> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..options.threads as u64)
+                .map(|t| {
+                    let starts = &starts;
+                    thread::Builder::new()
+                        .stack_size(STACK_SIZE)
+                        .spawn_scoped(scope, move || {
+                            let mut engines: [E; 2] =
+                                [options.first.build(), options.second.build()];
+                            (first + t..last)
+                                .step_by(options.threads)
+                                .map(|game| {
+                                    let sfen = &starts[(game / 2) as usize % starts.len()];
+                                    let result = play_game(
+                                        &mut engines,
+                                        sfen,
+                                        game % 2 == 0,
+                                        &options.limits,
+                                    );
+                                    (game, result)
+                                })
+                                .collect::<Vec<_>>()
+                        })
+                        .expect("failed to spawn match thread")
+                })
+                .collect();
+            handles
+                .into_iter()
+                .flat_map(|h| h.join().expect("match thread panicked"))
+                .collect()
+        });
+        for (game, result) in results {
+            stats.add(result, game % 2 == 0);
+        }
+        verdict = stats.verdict(&options.sprt);
+        println!(
+            "info string match {last}/{} {stats} llr {:.2}",
+            options.games,
+            stats.llr(&options.sprt)
+        );
+        first = last;
+    }
+    Ok((stats, verdict))
+}
+
+/// One game from `sfen`, `engines[0]` playing White when `first_is_white`.
+/// Unfinished games are draws.
+fn play_game<E, S, B, A, P, D, const FILE: usize, const BITBOARD_SIZE: usize, const RANK: usize>(
+    engines: &mut [E; 2],
+    sfen: &str,
+    first_is_white: bool,
+    limits: &SearchLimits,
+) -> GameResult
+where
+    E: Engine<S, B, A, P, D, FILE, BITBOARD_SIZE, RANK>,
+    S: Square + Hash + Send + 'static,
+    B: BitBoard<S>,
+    A: Attacks<S, B>,
+    P: Sized
+        + Display
+        + Clone
+        + Board<S, B, A>
+        + Sfen<S, B, A>
+        + Placement<S, B, A>
+        + Play<S, B, A>
+        + Rules<S, B, A>
+        + Send
+        + 'static,
+    D: EngineDefs<S, B, FILE>,
+{
+    let mut position = P::new();
+    if position.set_sfen(sfen).is_err() {
+        return GameResult::Aborted;
+    }
+    let [first, second] = engines;
+    let sides = if first_is_white {
+        [first, second]
+    } else {
+        [second, first]
+    };
+    fight(sides, &mut position, limits, MAX_FIGHT_PLIES, &mut vec![]).unwrap_or(GameResult::Draw)
+}
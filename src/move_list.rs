@@ -0,0 +1,257 @@
+use shuuro::{
+    Color, Move, Piece, PieceType, Square,
+    attacks::Attacks,
+    bitboard::BitBoard,
+    position::{Board, Placement, Play, Rules, Sfen},
+};
+use std::{collections::HashMap, hash::Hash};
+
+use crate::engine::JUMPERS;
+
+/// Most moves a side can have on any board. The richest shop, 870 credits
+/// in Shuuro Fairy, buys at most 35 pieces besides the king (18 pawns and
+/// 17 bishops or knights), promotion keeps the count, and no piece reaches
+/// more than the 43 squares of a central queen on 12x12: 35 * 43 + 8 = 1513.
+pub const MAX_MOVES: usize = 1536;
+
+/// Moves sorted by origin square, then target square. The crate hands out
+/// legal moves in a `HashMap`, whose iteration order changes from run to
+/// run; searching them in this order keeps node counts and chosen moves
+/// reproducible.
+#[derive(Clone, Debug)]
+pub struct MoveList<S: Square> {
+    moves: [(S, S); MAX_MOVES],
+    len: usize,
+}
+
+impl<S: Square> Default for MoveList<S> {
+    fn default() -> Self {
+        Self {
+            moves: [(S::default(), S::default()); MAX_MOVES],
+            len: 0,
+        }
+    }
+}
+
+impl<S: Square> MoveList<S> {
+    pub fn new<B: BitBoard<S>>(legal_moves: HashMap<S, B>) -> Self {
+        let mut list = Self::default();
+        for (from, targets) in legal_moves {
+            list.push_all(from, targets);
+        }
+        list.sort();
+        list
+    }
+
+    /// Legal moves of `color` in `position`.
+    pub fn legal<B, A, P>(position: &P, color: Color) -> Self
+    where
+        S: Hash,
+        B: BitBoard<S>,
+        A: Attacks<S, B>,
+        P: Board<S, B, A> + Sfen<S, B, A> + Placement<S, B, A> + Play<S, B, A> + Rules<S, B, A>,
+    {
+        Self::new(position.legal_moves(color))
+    }
+
+    /// Moves of `color` that follow the piece rules but may leave its king
+    /// in check. The same moves as the crate's candidates, before it
+    /// filters pins and checks, except for the Giraffe on 6x6 b3: the
+    /// crate's attacks reach past the board there and it finds no moves.
+    pub fn pseudo_legal<B, A, P>(position: &P, color: Color) -> Self
+    where
+        S: Hash,
+        B: BitBoard<S>,
+        A: Attacks<S, B>,
+        P: Board<S, B, A>,
+    {
+        Self::generate(position, color, !position.player_bb(color))
+    }
+
+    /// Pseudo-legal moves of `color` that capture a piece.
+    pub fn captures<B, A, P>(position: &P, color: Color) -> Self
+    where
+        S: Hash,
+        B: BitBoard<S>,
+        A: Attacks<S, B>,
+        P: Board<S, B, A>,
+    {
+        Self::generate(position, color, position.player_bb(color.flip()))
+    }
+
+    fn generate<B, A, P>(position: &P, color: Color, targets: B) -> Self
+    where
+        S: Hash,
+        B: BitBoard<S>,
+        A: Attacks<S, B>,
+        P: Board<S, B, A>,
+    {
+        let mut list = Self::default();
+        let plinths = position.player_bb(Color::NoColor);
+        let blockers = position.occupied_bb() | &plinths;
+        let them = position.player_bb(color.flip());
+        for from in position.player_bb(color) {
+            let Some(piece) = *position.piece_at(from) else {
+                continue;
+            };
+            let mut moves = attacks::<S, B, A>(&piece, &from, blockers) & &targets;
+            // Only jumpers land on plinths.
+            if !JUMPERS.contains(&piece.piece_type) {
+                moves &= &!plinths;
+            }
+            if piece.piece_type == PieceType::Pawn {
+                moves =
+                    (moves & &them) | &(pawn_pushes::<S, B, A>(from, color, blockers) & &targets);
+            }
+            list.push_all(from, moves);
+        }
+        list.sort();
+        list
+    }
+
+    fn push_all<B: BitBoard<S>>(&mut self, from: S, targets: B) {
+        for to in targets {
+            debug_assert!(self.len < MAX_MOVES, "more than {MAX_MOVES} moves");
+            self.moves[self.len] = (from, to);
+            self.len += 1;
+        }
+    }
+
+    fn sort(&mut self) {
+        self.moves[..self.len].sort_unstable_by_key(|(from, to)| (from.index(), to.index()));
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Move<S>> + '_ {
+        self.moves[..self.len]
+            .iter()
+            .map(|(from, to)| Move::new(*from, *to))
+    }
+
+    pub fn contains(&self, from: S, to: S) -> bool {
+        self.moves[..self.len].contains(&(from, to))
+    }
+}
+
+impl<S: Square> IntoIterator for MoveList<S> {
+    type Item = Move<S>;
+    type IntoIter = IntoIter<S>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            list: self,
+            index: 0,
+        }
+    }
+}
+
+pub struct IntoIter<S: Square> {
+    list: MoveList<S>,
+    index: usize,
+}
+
+impl<S: Square> Iterator for IntoIter<S> {
+    type Item = Move<S>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index == self.list.len {
+            return None;
+        }
+        let (from, to) = self.list.moves[self.index];
+        self.index += 1;
+        Some(Move::new(from, to))
+    }
+}
+
+/// Whether a piece of `by` attacks `square`. Each attack is looked up from
+/// `square` itself, as every piece but the pawn attacks both ways.
+pub fn is_attacked<S, B, A, P>(position: &P, square: S, by: Color) -> bool
+where
+    S: Square + Hash,
+    B: BitBoard<S>,
+    A: Attacks<S, B>,
+    P: Board<S, B, A>,
+{
+    let them = position.player_bb(by);
+    let blockers = position.occupied_bb() | &position.player_bb(Color::NoColor);
+    let pieces = |types: &[PieceType]| {
+        types
+            .iter()
+            .fold(B::empty(), |bb, pt| bb | &position.type_bb(pt))
+            & &them
+    };
+    let defender = by.flip();
+    let rooks = pieces(&[PieceType::Rook, PieceType::Queen, PieceType::Chancellor]);
+    let bishops = pieces(&[PieceType::Bishop, PieceType::Queen, PieceType::ArchBishop]);
+    let knights = pieces(&[
+        PieceType::Knight,
+        PieceType::Chancellor,
+        PieceType::ArchBishop,
+    ]);
+    (A::get_sliding_attacks(PieceType::Rook, &square, blockers) & &rooks).is_any()
+        || (A::get_sliding_attacks(PieceType::Bishop, &square, blockers) & &bishops).is_any()
+        || (A::get_non_sliding_attacks(PieceType::Knight, &square, defender, blockers) & &knights)
+            .is_any()
+        || (A::get_non_sliding_attacks(PieceType::King, &square, defender, blockers)
+            & &pieces(&[PieceType::King]))
+            .is_any()
+        || (A::get_non_sliding_attacks(PieceType::Pawn, &square, defender, blockers)
+            & &pieces(&[PieceType::Pawn]))
+            .is_any()
+        || (A::get_giraffe_attacks(&square) & &pieces(&[PieceType::Giraffe])).is_any()
+}
+
+/// Squares `piece` attacks from `square`, as the crate's rules see them.
+fn attacks<S: Square, B: BitBoard<S>, A: Attacks<S, B>>(
+    piece: &Piece,
+    square: &S,
+    blockers: B,
+) -> B {
+    match piece.piece_type {
+        PieceType::Rook | PieceType::Bishop | PieceType::Queen => {
+            A::get_sliding_attacks(piece.piece_type, square, blockers)
+        }
+        PieceType::Knight | PieceType::Pawn | PieceType::King => {
+            A::get_non_sliding_attacks(piece.piece_type, square, piece.color, blockers)
+        }
+        PieceType::Chancellor => {
+            A::get_non_sliding_attacks(PieceType::Knight, square, piece.color, blockers)
+                | &A::get_sliding_attacks(PieceType::Rook, square, blockers)
+        }
+        PieceType::ArchBishop => {
+            A::get_non_sliding_attacks(PieceType::Knight, square, piece.color, blockers)
+                | &A::get_sliding_attacks(PieceType::Bishop, square, blockers)
+        }
+        PieceType::Giraffe => A::get_giraffe_attacks(square) & &board::<S, B>(),
+        _ => B::empty(),
+    }
+}
+
+/// Every square of the board. The crate's Giraffe attacks can set bits past
+/// it, which stop the bitboard's iterator.
+fn board<S: Square, B: BitBoard<S>>() -> B {
+    S::iter().fold(B::empty(), |bb, sq| bb | &sq)
+}
+
+/// Pawn pushes from `square`: none when the square in front is taken, one
+/// when only the second is.
+fn pawn_pushes<S: Square, B: BitBoard<S>, A: Attacks<S, B>>(
+    square: S,
+    color: Color,
+    blockers: B,
+) -> B {
+    let pushes = A::get_pawn_moves(square.index(), color);
+    let mut blocked = pushes & &blockers;
+    match blocked.len() {
+        0 => pushes,
+        1 => A::between(square, blocked.pop().expect("one blocked square")),
+        _ => B::empty(),
+    }
+}
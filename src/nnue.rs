@@ -0,0 +1,464 @@
+use shuuro::{
+    Color, Move, Piece, PieceType, Square, attacks::Attacks, bitboard::BitBoard, position::Board,
+};
+use std::{
+    fs::File,
+    hash::Hash,
+    io::{self, BufReader, Read},
+    path::Path,
+    sync::Arc,
+};
+
+// Network file layout (all values little-endian):
+//
+//   magic "SNUE", version u32, squares u32, hidden u32, l2 u32, l3 u32
+//   ft_bias    [hidden]                          i16
+//   ft_weights [features][hidden]                i16
+//   l1_bias    [l2]                              i32
+//   l1_weights [l2][2 * hidden]                  i8
+//   l2_bias    [l3]                              i32
+//   l2_weights [l3][l2]                          i8
+//   out_bias                                     i32
+//   out_weights [l3]                             i8
+//
+// features = squares * PIECE_KINDS * squares (king square, piece kind, piece square).
+
+const MAGIC: &[u8; 4] = b"SNUE";
+const VERSION: u32 = 1;
+
+/// Queen, Rook, Bishop, Knight, Pawn, Chancellor, Archbishop and Giraffe for
+/// both sides, plus plinths. Kings only select the feature block.
+pub const PIECE_KINDS: usize = 17;
+pub const L2: usize = 32;
+pub const L3: usize = 32;
+
+const CLIP: i16 = 127;
+const WEIGHT_SCALE_BITS: i32 = 6;
+const OUTPUT_SCALE: i32 = 16;
+
+pub struct Network {
+    squares: usize,
+    dimension: usize,
+    hidden: usize,
+    ft_bias: Vec<i16>,
+    ft_weights: Vec<i16>,
+    l1_bias: Vec<i32>,
+    l1_weights: Vec<i8>,
+    l2_bias: Vec<i32>,
+    l2_weights: Vec<i8>,
+    out_bias: i32,
+    out_weights: Vec<i8>,
+}
+
+impl Network {
+    /// Loads weights from `path`. The file has to be built for a board with
+    /// `squares` squares.
+    pub fn load<T: AsRef<Path>>(path: T, squares: usize) -> io::Result<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid("not a network file"));
+        }
+        if read_u32(&mut reader)? != VERSION {
+            return Err(invalid("unsupported network version"));
+        }
+        if read_u32(&mut reader)? as usize != squares {
+            return Err(invalid("network was built for another board size"));
+        }
+        let hidden = read_u32(&mut reader)? as usize;
+        if hidden == 0 || !hidden.is_multiple_of(32) {
+            return Err(invalid("hidden size must be a multiple of 32"));
+        }
+        if read_u32(&mut reader)? as usize != L2 || read_u32(&mut reader)? as usize != L3 {
+            return Err(invalid("unsupported layer sizes"));
+        }
+        let dimension = (1..=squares)
+            .find(|d| d * d == squares)
+            .ok_or_else(|| invalid("board is not square"))?;
+        let features = squares * PIECE_KINDS * squares;
+
+        Ok(Self {
+            squares,
+            dimension,
+            hidden,
+            ft_bias: read_i16s(&mut reader, hidden)?,
+            ft_weights: read_i16s(&mut reader, features * hidden)?,
+            l1_bias: read_i32s(&mut reader, L2)?,
+            l1_weights: read_i8s(&mut reader, L2 * 2 * hidden)?,
+            l2_bias: read_i32s(&mut reader, L3)?,
+            l2_weights: read_i8s(&mut reader, L3 * L2)?,
+            out_bias: read_i32s(&mut reader, 1)?[0],
+            out_weights: read_i8s(&mut reader, L3)?,
+        })
+    }
+
+    pub fn hidden(&self) -> usize {
+        self.hidden
+    }
+
+    fn orient(&self, sq: usize, perspective: Color) -> usize {
+        match perspective {
+            Color::Black => {
+                let d = self.dimension;
+                (d - 1 - sq / d) * d + sq % d
+            }
+            _ => sq,
+        }
+    }
+
+    fn piece_kind(piece: Piece, perspective: Color) -> Option<usize> {
+        match piece.piece_type {
+            PieceType::King => None,
+            PieceType::Plinth => Some(PIECE_KINDS - 1),
+            pt => {
+                let kind = pt.index() - 1;
+                if piece.color == perspective {
+                    Some(kind)
+                } else {
+                    Some(kind + 8)
+                }
+            }
+        }
+    }
+
+    fn feature(&self, king: usize, piece: Piece, sq: usize, perspective: Color) -> Option<usize> {
+        let kind = Self::piece_kind(piece, perspective)?;
+        let king = self.orient(king, perspective);
+        Some((king * PIECE_KINDS + kind) * self.squares + self.orient(sq, perspective))
+    }
+
+    fn weights(&self, feature: usize) -> &[i16] {
+        &self.ft_weights[feature * self.hidden..(feature + 1) * self.hidden]
+    }
+
+    fn propagate(&self, accumulator: &Accumulator, stm: Color) -> i32 {
+        let mut input = vec![0u8; 2 * self.hidden];
+        for (i, perspective) in [stm, stm.flip()].into_iter().enumerate() {
+            let values = &accumulator.values[perspective.index()];
+            for (out, v) in input[i * self.hidden..].iter_mut().zip(values) {
+                *out = (*v).clamp(0, CLIP) as u8;
+            }
+        }
+
+        let mut hidden1 = [0u8; L2];
+        for (o, out) in hidden1.iter_mut().enumerate() {
+            let row = &self.l1_weights[o * 2 * self.hidden..(o + 1) * 2 * self.hidden];
+            let sum = self.l1_bias[o] + simd::dot(&input, row);
+            *out = (sum >> WEIGHT_SCALE_BITS).clamp(0, CLIP as i32) as u8;
+        }
+
+        let mut hidden2 = [0u8; L3];
+        for (o, out) in hidden2.iter_mut().enumerate() {
+            let row = &self.l2_weights[o * L2..(o + 1) * L2];
+            let sum = self.l2_bias[o] + simd::dot(&hidden1, row);
+            *out = (sum >> WEIGHT_SCALE_BITS).clamp(0, CLIP as i32) as u8;
+        }
+
+        (self.out_bias + simd::dot(&hidden2, &self.out_weights)) / OUTPUT_SCALE
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Accumulator {
+    values: [Vec<i16>; 2],
+}
+
+/// Accumulator stack for one search. Every made move pushes a new
+/// accumulator derived from its parent, unmaking a move pops it again.
+pub struct Nnue {
+    network: Arc<Network>,
+    stack: Vec<Accumulator>,
+    top: usize,
+}
+
+impl Nnue {
+    pub fn new(network: Arc<Network>) -> Self {
+        let hidden = network.hidden;
+        Self {
+            network,
+            stack: vec![Accumulator {
+                values: [vec![0; hidden], vec![0; hidden]],
+            }],
+            top: 0,
+        }
+    }
+
+    pub fn network(&self) -> &Arc<Network> {
+        &self.network
+    }
+
+    /// Rebuilds the root accumulator from scratch.
+    pub fn refresh<S, B, A, P>(&mut self, position: &P)
+    where
+        S: Square + Hash,
+        B: BitBoard<S>,
+        A: Attacks<S, B>,
+        P: Board<S, B, A>,
+    {
+        self.top = 0;
+        self.refresh_top(position);
+    }
+
+    /// Derives the accumulator of `child` from the one of `parent`.
+    pub fn push<S, B, A, P>(&mut self, parent: &P, child: &P, mv: &Move<S>)
+    where
+        S: Square + Hash,
+        B: BitBoard<S>,
+        A: Attacks<S, B>,
+        P: Board<S, B, A>,
+    {
+        self.top += 1;
+        if self.stack.len() == self.top {
+            let parent = self.stack[self.top - 1].clone();
+            self.stack.push(parent);
+        } else {
+            let (done, rest) = self.stack.split_at_mut(self.top);
+            for perspective in 0..2 {
+                rest[0].values[perspective]
+                    .copy_from_slice(&done[self.top - 1].values[perspective]);
+            }
+        }
+
+        let Move::Normal { from, to, .. } = mv else {
+            self.refresh_top(child);
+            return;
+        };
+        let (Some(moved), Some(placed)) = (*parent.piece_at(*from), *child.piece_at(*to)) else {
+            self.refresh_top(child);
+            return;
+        };
+        let captured = *parent.piece_at(*to);
+
+        for perspective in [Color::White, Color::Black] {
+            if moved.piece_type == PieceType::King && moved.color == perspective {
+                self.refresh_perspective(child, perspective);
+                continue;
+            }
+            let Some(king) = child.find_king(perspective) else {
+                continue;
+            };
+            let network = &self.network;
+            let king = king.index();
+            let values = &mut self.stack[self.top].values[perspective.index()];
+            if let Some(f) = network.feature(king, moved, from.index(), perspective) {
+                simd::sub_assign(values, network.weights(f));
+            }
+            if let Some(f) =
+                captured.and_then(|c| network.feature(king, c, to.index(), perspective))
+            {
+                simd::sub_assign(values, network.weights(f));
+            }
+            if let Some(f) = network.feature(king, placed, to.index(), perspective) {
+                simd::add_assign(values, network.weights(f));
+            }
+        }
+    }
+
+    /// Accumulator of the current position.
+    pub fn accumulator(&self) -> &Accumulator {
+        &self.stack[self.top]
+    }
+
+    pub fn pop(&mut self) {
+        self.top = self.top.saturating_sub(1);
+    }
+
+    /// Evaluation of the current accumulator from the side to move's point of view.
+    pub fn evaluate(&self, stm: Color) -> i32 {
+        self.network.propagate(&self.stack[self.top], stm)
+    }
+
+    fn refresh_top<S, B, A, P>(&mut self, position: &P)
+    where
+        S: Square + Hash,
+        B: BitBoard<S>,
+        A: Attacks<S, B>,
+        P: Board<S, B, A>,
+    {
+        for perspective in [Color::White, Color::Black] {
+            self.refresh_perspective(position, perspective);
+        }
+    }
+
+    fn refresh_perspective<S, B, A, P>(&mut self, position: &P, perspective: Color)
+    where
+        S: Square + Hash,
+        B: BitBoard<S>,
+        A: Attacks<S, B>,
+        P: Board<S, B, A>,
+    {
+        let network = &self.network;
+        let values = &mut self.stack[self.top].values[perspective.index()];
+        values.copy_from_slice(&network.ft_bias);
+        let Some(king) = position.find_king(perspective) else {
+            return;
+        };
+        let king = king.index();
+        let pieces = position.player_bb(Color::White) | &position.player_bb(Color::Black);
+        for sq in pieces {
+            let Some(piece) = *position.piece_at(sq) else {
+                continue;
+            };
+            if let Some(f) = network.feature(king, piece, sq.index(), perspective) {
+                simd::add_assign(values, network.weights(f));
+            }
+        }
+        let plinth = Piece {
+            piece_type: PieceType::Plinth,
+            color: Color::NoColor,
+        };
+        for sq in position.player_bb(Color::NoColor) {
+            if let Some(f) = network.feature(king, plinth, sq.index(), perspective) {
+                simd::add_assign(values, network.weights(f));
+            }
+        }
+    }
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn read_bytes<R: Read>(reader: &mut R, len: usize) -> io::Result<Vec<u8>> {
+    let mut buf = vec![0; len];
+    reader.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+    let mut buf = [0; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_i8s<R: Read>(reader: &mut R, len: usize) -> io::Result<Vec<i8>> {
+    Ok(read_bytes(reader, len)?
+        .into_iter()
+        .map(|b| b as i8)
+        .collect())
+}
+
+fn read_i16s<R: Read>(reader: &mut R, len: usize) -> io::Result<Vec<i16>> {
+    let bytes = read_bytes(reader, len * 2)?;
+    Ok(bytes
+        .chunks_exact(2)
+        .map(|c| i16::from_le_bytes([c[0], c[1]]))
+        .collect())
+}
+
+fn read_i32s<R: Read>(reader: &mut R, len: usize) -> io::Result<Vec<i32>> {
+    let bytes = read_bytes(reader, len * 4)?;
+    Ok(bytes
+        .chunks_exact(4)
+        .map(|c| i32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+        .collect())
+}
+
+/// Integer kernels. AVX2 is picked at runtime, the scalar versions give
+/// bit-identical results everywhere else.
+pub mod simd {
+    pub fn add_assign(acc: &mut [i16], weights: &[i16]) {
+        #[cfg(target_arch = "x86_64")]
+        if is_x86_feature_detected!("avx2") {
+            // SAFETY: avx2 is available and both slices have the same length.
+            unsafe { avx2::add_assign(acc, weights) };
+            return;
+        }
+        scalar::add_assign(acc, weights);
+    }
+
+    pub fn sub_assign(acc: &mut [i16], weights: &[i16]) {
+        #[cfg(target_arch = "x86_64")]
+        if is_x86_feature_detected!("avx2") {
+            // SAFETY: avx2 is available and both slices have the same length.
+            unsafe { avx2::sub_assign(acc, weights) };
+            return;
+        }
+        scalar::sub_assign(acc, weights);
+    }
+
+    pub fn dot(input: &[u8], weights: &[i8]) -> i32 {
+        #[cfg(target_arch = "x86_64")]
+        if is_x86_feature_detected!("avx2") && input.len().is_multiple_of(32) {
+            // SAFETY: avx2 is available and both slices have the same length.
+            return unsafe { avx2::dot(input, weights) };
+        }
+        scalar::dot(input, weights)
+    }
+
+    pub mod scalar {
+        pub fn add_assign(acc: &mut [i16], weights: &[i16]) {
+            for (a, w) in acc.iter_mut().zip(weights) {
+                *a = a.wrapping_add(*w);
+            }
+        }
+
+        pub fn sub_assign(acc: &mut [i16], weights: &[i16]) {
+            for (a, w) in acc.iter_mut().zip(weights) {
+                *a = a.wrapping_sub(*w);
+            }
+        }
+
+        pub fn dot(input: &[u8], weights: &[i8]) -> i32 {
+            input
+                .iter()
+                .zip(weights)
+                .map(|(i, w)| *i as i32 * *w as i32)
+                .sum()
+        }
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    mod avx2 {
+        use std::arch::x86_64::*;
+
+        #[target_feature(enable = "avx2")]
+        pub unsafe fn add_assign(acc: &mut [i16], weights: &[i16]) {
+            debug_assert_eq!(acc.len(), weights.len());
+            for (a, w) in acc.chunks_exact_mut(16).zip(weights.chunks_exact(16)) {
+                unsafe {
+                    let va = _mm256_loadu_si256(a.as_ptr() as *const __m256i);
+                    let vw = _mm256_loadu_si256(w.as_ptr() as *const __m256i);
+                    _mm256_storeu_si256(a.as_mut_ptr() as *mut __m256i, _mm256_add_epi16(va, vw));
+                }
+            }
+        }
+
+        #[target_feature(enable = "avx2")]
+        pub unsafe fn sub_assign(acc: &mut [i16], weights: &[i16]) {
+            debug_assert_eq!(acc.len(), weights.len());
+            for (a, w) in acc.chunks_exact_mut(16).zip(weights.chunks_exact(16)) {
+                unsafe {
+                    let va = _mm256_loadu_si256(a.as_ptr() as *const __m256i);
+                    let vw = _mm256_loadu_si256(w.as_ptr() as *const __m256i);
+                    _mm256_storeu_si256(a.as_mut_ptr() as *mut __m256i, _mm256_sub_epi16(va, vw));
+                }
+            }
+        }
+
+        /// Inputs are clipped to 0..=127, so `maddubs` never saturates.
+        #[target_feature(enable = "avx2")]
+        pub unsafe fn dot(input: &[u8], weights: &[i8]) -> i32 {
+            debug_assert_eq!(input.len(), weights.len());
+            let ones = _mm256_set1_epi16(1);
+            let mut sum = _mm256_setzero_si256();
+            for (i, w) in input.chunks_exact(32).zip(weights.chunks_exact(32)) {
+                let (vi, vw) = unsafe {
+                    (
+                        _mm256_loadu_si256(i.as_ptr() as *const __m256i),
+                        _mm256_loadu_si256(w.as_ptr() as *const __m256i),
+                    )
+                };
+                let products = _mm256_madd_epi16(_mm256_maddubs_epi16(vi, vw), ones);
+                sum = _mm256_add_epi32(sum, products);
+            }
+            let lo = _mm256_castsi256_si128(sum);
+            let hi = _mm256_extracti128_si256(sum, 1);
+            let sum = _mm_add_epi32(lo, hi);
+            let sum = _mm_add_epi32(sum, _mm_shuffle_epi32(sum, 0b01_00_11_10));
+            let sum = _mm_add_epi32(sum, _mm_shuffle_epi32(sum, 0b10_11_00_01));
+            _mm_cvtsi128_si32(sum)
+        }
+    }
+}
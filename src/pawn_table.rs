@@ -0,0 +1,44 @@
+use std::cell::Cell;
+
+pub const PAWN_TABLE_SIZE: usize = 1 << 14;
+
+/// Cached pawn-structure score, from White's point of view.
+#[derive(Clone, Copy, Debug)]
+pub struct PawnEntry {
+    pub key: u64,
+    pub score: i32,
+}
+
+/// Always-replace table keyed by the zobrist hash of the pawns. Evaluation
+/// only has `&self`, so slots are cells.
+pub struct PawnTable {
+    entries: Box<[Cell<Option<PawnEntry>>]>,
+}
+
+impl PawnTable {
+    pub fn new(size: usize) -> Self {
+        Self {
+            entries: (0..size.next_power_of_two())
+                .map(|_| Cell::new(None))
+                .collect(),
+        }
+    }
+
+    fn slot(&self, key: u64) -> &Cell<Option<PawnEntry>> {
+        &self.entries[key as usize & (self.entries.len() - 1)]
+    }
+
+    pub fn probe(&self, key: u64) -> Option<PawnEntry> {
+        self.slot(key).get().filter(|entry| entry.key == key)
+    }
+
+    pub fn store(&self, entry: PawnEntry) {
+        self.slot(entry.key).set(Some(entry));
+    }
+}
+
+impl Default for PawnTable {
+    fn default() -> Self {
+        Self::new(PAWN_TABLE_SIZE)
+    }
+}
@@ -0,0 +1,92 @@
+use shuuro::PieceType;
+
+// A personality weights the groups of terms of the handcrafted evaluation,
+// in percent of their normal value, and can value some pieces above their
+// table value. NNUE evaluation is left as it is.
+
+/// Groups of `evaluate_position` terms a personality can weight.
+#[derive(Clone, Copy, Debug)]
+pub enum EvalGroup {
+    PieceSquares,
+    PawnStructure,
+    Mobility,
+    KingSafety,
+    Plinths,
+    Fairy,
+    /// Bishop pair, rooks on open files and knight outposts.
+    Positional,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Personality {
+    pub name: &'static str,
+    /// Percent weights, indexed by `EvalGroup`.
+    pub weights: [i32; 7],
+    /// Percent of the table value of each piece, indexed by `PieceType`.
+    pub material: [i32; 9],
+}
+
+impl Personality {
+    pub fn scale(&self, group: EvalGroup, score: i32) -> i32 {
+        score * self.weights[group as usize] / 100
+    }
+
+    pub fn piece_value(&self, piece_type: PieceType, value: i32) -> i32 {
+        value * self.material[piece_type.index()] / 100
+    }
+
+    pub fn by_name(name: &str) -> Option<&'static Self> {
+        PERSONALITIES
+            .iter()
+            .find(|personality| personality.name.eq_ignore_ascii_case(name))
+    }
+}
+
+impl Default for Personality {
+    fn default() -> Self {
+        PERSONALITIES[0]
+    }
+}
+
+// Weights: PieceSquares, PawnStructure, Mobility, KingSafety, Plinths, Fairy, Positional
+// Material: King, Queen, Rook, Bishop, Knight, Pawn, Chancellor, Archbishop, Giraffe
+pub const PERSONALITIES: [Personality; 4] = [
+    Personality {
+        name: "Default",
+        weights: [100, 100, 100, 100, 100, 100, 100],
+        material: [100; 9],
+    },
+    // Goes for the king and keeps its pieces active.
+    Personality {
+        name: "Aggressive",
+        weights: [100, 80, 140, 160, 100, 100, 90],
+        material: [100; 9],
+    },
+    // Cares for pawn structure and outposts, on plinths too. Pawn structure
+    // stays near its normal weight: much above it, the chain bonus starts to
+    // outweigh material and self-play games are lost.
+    Personality {
+        name: "Positional",
+        weights: [100, 115, 100, 95, 120, 100, 150],
+        material: [100; 9],
+    },
+    // Overvalues Chancellors and Archbishops and plays for them.
+    Personality {
+        name: "FairyLover",
+        weights: [100, 100, 100, 100, 100, 150, 100],
+        material: [100, 100, 100, 100, 100, 100, 125, 125, 100],
+    },
+];
+
+/// `option` line of the `Personality` combo, every profile a choice.
+pub fn uci_option() -> String {
+    let names: Vec<String> = PERSONALITIES
+        .iter()
+        .map(|personality| format!("var {}", personality.name))
+        .collect();
+    format!(
+        "option name Personality type combo default {} {}",
+        PERSONALITIES[0].name,
+        names.join(" ")
+    )
+}
@@ -0,0 +1,40 @@
+/// Small xorshift64* generator. Everything that has to be reproducible from
+/// a seed (self-play, zobrist keys) draws from this instead of `rand`.
+#[derive(Clone, Debug)]
+pub struct Prng {
+    state: u64,
+}
+
+impl Prng {
+    pub const fn new(seed: u64) -> Self {
+        Self {
+            state: splitmix(seed),
+        }
+    }
+
+    /// Generator for the `index`-th independent stream of `seed`.
+    pub const fn stream(seed: u64, index: u64) -> Self {
+        Self::new(splitmix(seed) ^ splitmix(index.wrapping_add(0x51_7c_c1_b7_27_22_0a_95)))
+    }
+
+    pub const fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// Uniform value in `0..n`.
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+}
+
+const fn splitmix(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    let z = z ^ (z >> 31);
+    // xorshift must never start from zero
+    if z == 0 { 1 } else { z }
+}
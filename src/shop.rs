@@ -0,0 +1,125 @@
+use shuuro::{Color, Move, Piece, PieceType, Selection, Square, Variant};
+use std::str::FromStr;
+
+const PIECE_TYPES: [PieceType; 9] = [
+    PieceType::King,
+    PieceType::Queen,
+    PieceType::Rook,
+    PieceType::Bishop,
+    PieceType::Knight,
+    PieceType::Pawn,
+    PieceType::Chancellor,
+    PieceType::ArchBishop,
+    PieceType::Giraffe,
+];
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ShopStrategy {
+    #[default]
+    Balanced,
+    Aggressive,
+    PawnHeavy,
+}
+
+impl ShopStrategy {
+    /// Share of the budget for each piece type
+    /// (King, Queen, Rook, Bishop, Knight, Pawn, Chancellor, Archbishop, Giraffe).
+    fn weights(&self) -> [i32; 9] {
+        match self {
+            Self::Balanced => [0, 20, 22, 14, 14, 16, 6, 5, 3],
+            Self::Aggressive => [0, 30, 18, 10, 10, 8, 12, 10, 2],
+            Self::PawnHeavy => [0, 15, 15, 10, 10, 40, 4, 4, 2],
+        }
+    }
+}
+
+impl FromStr for ShopStrategy {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "balanced" => Ok(Self::Balanced),
+            "aggressive" => Ok(Self::Aggressive),
+            "pawnheavy" | "pawn-heavy" => Ok(Self::PawnHeavy),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Picks an army for the shop phase. Purchases go through the crate's
+/// `Selection`, so credit and piece limits are the ones the shop enforces.
+#[derive(Clone, Copy, Debug)]
+pub struct ShopAdvisor {
+    variant: Variant,
+    strategy: ShopStrategy,
+}
+
+impl ShopAdvisor {
+    pub fn new(variant: Variant, strategy: ShopStrategy) -> Self {
+        Self { variant, strategy }
+    }
+
+    /// Purchase sequence for `color` starting from an empty shop.
+    pub fn purchases<S: Square>(&self, color: Color) -> Vec<Move<S>> {
+        let mut selection = Selection::default();
+        selection.update_variant(self.variant);
+        self.buy(&mut selection, color)
+    }
+
+    /// Spends the credit `color` has left in `selection`, always buying the
+    /// piece type furthest below its share of the budget.
+    pub fn buy<S: Square>(&self, selection: &mut Selection<S>, color: Color) -> Vec<Move<S>> {
+        let mut weights = self.strategy.weights();
+        for (index, weight) in weights.iter_mut().enumerate() {
+            if !self.variant.can_select(&PIECE_TYPES[index]) {
+                *weight = 0;
+            }
+        }
+        let prices = PIECE_TYPES.map(|piece_type| price::<S>(self.variant, piece_type));
+        let total_weight: i32 = weights.iter().sum();
+        let budget = selection.credit(color);
+        let mut spent = [0; 9];
+        let mut exhausted = weights.map(|weight| weight == 0);
+        let mut purchases = vec![];
+
+        loop {
+            let credit = selection.credit(color);
+            let best = (0..9)
+                .filter(|&index| !exhausted[index] && prices[index] <= credit)
+                .max_by_key(|&index| {
+                    let target = budget * weights[index] / total_weight;
+                    (target - spent[index], prices[index])
+                });
+            let Some(index) = best else {
+                break;
+            };
+            let piece = Piece {
+                piece_type: PIECE_TYPES[index],
+                color,
+            };
+            let mv = Move::Select { piece };
+            if selection.play(mv.clone()).is_some() {
+                spent[index] += prices[index];
+                purchases.push(mv);
+            } else {
+                exhausted[index] = true;
+            }
+        }
+        selection.confirm(color);
+        purchases
+    }
+}
+
+/// Price of `piece_type` in the shop of `variant`. The crate keeps its price
+/// table private, so this buys one from a fresh shop and reads the credit.
+pub fn price<S: Square>(variant: Variant, piece_type: PieceType) -> i32 {
+    let mut selection = Selection::<S>::default();
+    selection.update_variant(variant);
+    let credit = selection.credit(Color::White);
+    let piece = Piece {
+        piece_type,
+        color: Color::White,
+    };
+    selection.play(Move::Select { piece });
+    credit - selection.credit(Color::White)
+}
@@ -0,0 +1,133 @@
+use shuuro::{Color, Move, Square};
+
+use crate::{engine::SearchLimits, prng::Prng};
+
+// Below the top level the engine is weakened three ways: its search is
+// capped in depth and nodes, it picks among its best root moves at random,
+// weighted by score, and its static evaluation is blurred by noise. The
+// caps depend on the board, so each one has its own table, calibrated with
+// the self-play runner, e.g. `match first UCI_LimitStrength=true,UCI_Elo=1120
+// second UCI_LimitStrength=true,UCI_Elo=800 nodes 2000000`. The measured
+// gaps sit next to the tables: four levels, 320 `UCI_Elo` apart, are 80
+// to 210 Elo apart on 6x6 and 8x8 and 160 to 340 on 12x12. Node caps do
+// most of the work; the depth caps only bound searches without other
+// limits.
+
+/// Full strength.
+pub const MAX_LEVEL: u8 = 20;
+/// `UCI_Elo` range, spread evenly over the levels.
+pub const MIN_ELO: u32 = 800;
+pub const MAX_ELO: u32 = 2400;
+
+/// Search caps of one board for every level below `MAX_LEVEL`, weakest
+/// first.
+#[derive(Clone, Copy, Debug)]
+pub struct SkillCaps {
+    pub depth: [i32; MAX_LEVEL as usize],
+    pub nodes: [u64; MAX_LEVEL as usize],
+}
+
+/// `Skill Level`, `UCI_LimitStrength` and `UCI_Elo` settings of an engine.
+#[derive(Clone, Debug)]
+pub struct SkillState {
+    pub level: u8,
+    pub limit_strength: bool,
+    pub elo: u32,
+    prng: Prng,
+    noise_seed: u64,
+}
+
+impl Default for SkillState {
+    fn default() -> Self {
+        Self {
+            level: MAX_LEVEL,
+            limit_strength: false,
+            elo: MAX_ELO,
+            prng: Prng::new(0),
+            noise_seed: 0,
+        }
+    }
+}
+
+impl SkillState {
+    /// Level in use, taken from `elo` when strength is limited.
+    pub fn level(&self) -> u8 {
+        if !self.limit_strength {
+            return self.level.min(MAX_LEVEL);
+        }
+        let elo = self.elo.clamp(MIN_ELO, MAX_ELO) - MIN_ELO;
+        (elo * MAX_LEVEL as u32 / (MAX_ELO - MIN_ELO)) as u8
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.level() < MAX_LEVEL
+    }
+
+    /// `limits` within the caps of the level.
+    pub fn limits(&self, limits: SearchLimits, caps: &SkillCaps) -> SearchLimits {
+        if !self.enabled() {
+            return limits;
+        }
+        let level = self.level() as usize;
+        let nodes = limits
+            .nodes
+            .map_or(caps.nodes[level], |n| n.min(caps.nodes[level]));
+        SearchLimits {
+            depth: Some(limits.max_depth().min(caps.depth[level])),
+            nodes: Some(nodes),
+            movetime: limits.movetime,
+        }
+    }
+
+    /// Root moves the pick is made from.
+    pub fn candidates(&self) -> usize {
+        1 + (MAX_LEVEL - self.level()) as usize / 4
+    }
+
+    /// Draws the noise of the next search, so the same position can be
+    /// judged differently from one move to the next.
+    pub fn new_search(&mut self) {
+        self.noise_seed = self.prng.next_u64();
+    }
+
+    /// Evaluation noise in centipawns for the position with `key`, the same
+    /// for the whole search.
+    pub fn noise(&self, key: u64) -> i32 {
+        let amplitude = 8 * (MAX_LEVEL - self.level()) as u64;
+        if amplitude == 0 {
+            return 0;
+        }
+        let draw = Prng::new(key ^ self.noise_seed).next_u64() % (2 * amplitude + 1);
+        draw as i32 - amplitude as i32
+    }
+
+    /// One of `candidates`, best first with White's scores, chosen at random
+    /// with weights that fall off with the distance to the best score. The
+    /// lower the level, the slower they fall.
+    pub fn pick<S: Square>(
+        &mut self,
+        candidates: &[(Move<S>, i32)],
+        player: Color,
+    ) -> Option<(Move<S>, i32)> {
+        let (_, best) = candidates.first()?;
+        let temperature = 10.0 + 5.0 * (MAX_LEVEL - self.level()) as f64;
+        let weights: Vec<u64> = candidates
+            .iter()
+            .map(|(_, score)| {
+                let loss = match player {
+                    Color::White => best.saturating_sub(*score),
+                    _ => score.saturating_sub(*best),
+                };
+                (1000.0 * (-(loss as f64) / temperature).exp()) as u64
+            })
+            .collect();
+        let mut draw = self.prng.below(weights.iter().sum::<u64>() as usize) as u64;
+        for (candidate, weight) in candidates.iter().zip(weights) {
+            if draw < weight {
+                return Some(candidate.clone());
+            }
+            draw -= weight;
+        }
+        candidates.last().cloned()
+    }
+}
@@ -0,0 +1,631 @@
+use shuuro::{
+    Color, PieceType, Square,
+    attacks::Attacks,
+    bitboard::BitBoard,
+    position::Board,
+    shuuro6::{attacks6::Attacks6, bitboard6::BB6, position6::P6, square6::Square6},
+};
+use std::{
+    collections::HashMap,
+    fmt, fs, io,
+    io::Write,
+    path::{Path, PathBuf},
+    str::FromStr,
+    thread,
+};
+
+use crate::engine::JUMPERS;
+
+/// Most pieces in a table, kings included.
+pub const MAX_PIECES: usize = 4;
+
+/// Score of a tablebase win with mate on the board, above any evaluation.
+pub const TB_WIN: i32 = 20_000;
+
+const SQUARES: usize = 36;
+const BOARD: u64 = (1 << SQUARES) - 1;
+const NONE: u8 = u8::MAX;
+
+const WDL_MAGIC: &[u8; 4] = b"S6WD";
+const DTM_MAGIC: &[u8; 4] = b"S6DM";
+/// Bumped whenever the move rules change. Version 1 tables kept the crate's
+/// Giraffe leaving the board from b3 and are rejected.
+const VERSION: u8 = 2;
+
+// WDL codes for the side to move, one byte per position.
+const DRAW: u8 = 0;
+const WIN: u8 = 1;
+const LOSS: u8 = 2;
+const INVALID: u8 = 3;
+const UNRESOLVED: u8 = 4;
+
+/// Non-king pieces a table can hold, in `PieceType` order.
+const PIECE_ORDER: [PieceType; 7] = [
+    PieceType::Queen,
+    PieceType::Rook,
+    PieceType::Bishop,
+    PieceType::Knight,
+    PieceType::Chancellor,
+    PieceType::ArchBishop,
+    PieceType::Giraffe,
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Wdl {
+    Loss,
+    Draw,
+    Win,
+}
+
+/// Tablebase result for the side to move.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Probe {
+    pub wdl: Wdl,
+    /// Plies to mate with best play, 0 for draws.
+    pub dtm: u8,
+}
+
+impl Probe {
+    /// Search score for the side to move, faster mates score higher.
+    pub fn score(&self) -> i32 {
+        match self.wdl {
+            Wdl::Win => TB_WIN - self.dtm as i32,
+            Wdl::Loss => self.dtm as i32 - TB_WIN,
+            Wdl::Draw => 0,
+        }
+    }
+}
+
+/// Material of a pawnless table, such as `KRvKN`. Each side has its king
+/// first and the other pieces in `PieceType` order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Signature {
+    sides: [Vec<PieceType>; 2],
+}
+
+impl Signature {
+    /// Every piece with its colour, in index order.
+    fn pieces(&self) -> Vec<(PieceType, Color)> {
+        let mut pieces = vec![];
+        for (side, color) in [Color::White, Color::Black].into_iter().enumerate() {
+            pieces.push((PieceType::King, color));
+            pieces.extend(self.sides[side].iter().map(|pt| (*pt, color)));
+        }
+        pieces
+    }
+
+    /// Signature after piece `index` of `pieces()` is captured.
+    fn without(&self, index: usize) -> Option<Self> {
+        let white = self.sides[0].len() + 1;
+        let (side, at) = match index {
+            0 => return None,
+            i if i < white => (0, i - 1),
+            i if i == white => return None,
+            i => (1, i - white - 1),
+        };
+        let mut signature = self.clone();
+        signature.sides[side].remove(at);
+        Some(signature)
+    }
+
+    /// Number of positions: side to move times a square per piece.
+    fn size(&self) -> usize {
+        2 * SQUARES.pow(self.pieces().len() as u32)
+    }
+}
+
+impl FromStr for Signature {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (white, black) = s.split_once(['v', 'V']).ok_or(())?;
+        let mut sides = [vec![], vec![]];
+        for (side, letters) in [white, black].into_iter().enumerate() {
+            let mut letters = letters.chars();
+            if letters.next().and_then(PieceType::from_sfen) != Some(PieceType::King) {
+                return Err(());
+            }
+            for c in letters {
+                match PieceType::from_sfen(c) {
+                    Some(pt) if PIECE_ORDER.contains(&pt) => sides[side].push(pt),
+                    _ => return Err(()),
+                }
+            }
+            sides[side].sort_by_key(|pt| pt.index());
+        }
+        if sides[0].len() + sides[1].len() + 2 > MAX_PIECES {
+            return Err(());
+        }
+        Ok(Self { sides })
+    }
+}
+
+impl fmt::Display for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (side, pieces) in self.sides.iter().enumerate() {
+            if side == 1 {
+                write!(f, "v")?;
+            }
+            write!(f, "K")?;
+            for pt in pieces {
+                write!(f, "{}", pt.to_string().to_uppercase())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// WDL and DTM values of every position of one signature on one plinth layout.
+pub struct Table {
+    signature: Signature,
+    plinths: u64,
+    wdl: Vec<u8>,
+    dtm: Vec<u8>,
+}
+
+impl Table {
+    pub fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    pub fn plinths(&self) -> u64 {
+        self.plinths
+    }
+
+    /// Longest mate in the table, in plies.
+    pub fn max_dtm(&self) -> u8 {
+        self.dtm.iter().copied().max().unwrap_or(0)
+    }
+
+    /// Results of every valid position with `stm` to move.
+    pub fn results(&self, stm: Color) -> impl Iterator<Item = Probe> + '_ {
+        let half = self.wdl.len() / 2;
+        let start = if stm == Color::Black { half } else { 0 };
+        (start..start + half).filter_map(|index| self.probe_index(index))
+    }
+
+    fn probe(&self, stm: Color, squares: &[u8]) -> Option<Probe> {
+        self.probe_index(encode(stm, squares.iter().copied()))
+    }
+
+    fn probe_index(&self, index: usize) -> Option<Probe> {
+        let wdl = match self.wdl[index] {
+            WIN => Wdl::Win,
+            LOSS => Wdl::Loss,
+            DRAW => Wdl::Draw,
+            _ => return None,
+        };
+        Some(Probe {
+            wdl,
+            dtm: self.dtm[index],
+        })
+    }
+
+    fn file_name(&self) -> String {
+        format!("{}-{:09x}", self.signature, self.plinths)
+    }
+
+    /// Writes `<signature>-<plinths>.s6w` and `.s6m` into `dir`.
+    pub fn write<T: AsRef<Path>>(&self, dir: T) -> io::Result<()> {
+        let path = dir.as_ref().join(self.file_name());
+        self.write_file(&path.with_extension("s6w"), WDL_MAGIC, &self.wdl)?;
+        self.write_file(&path.with_extension("s6m"), DTM_MAGIC, &self.dtm)
+    }
+
+    fn write_file(&self, path: &Path, magic: &[u8; 4], values: &[u8]) -> io::Result<()> {
+        let signature = self.signature.to_string();
+        let mut file = io::BufWriter::new(fs::File::create(path)?);
+        file.write_all(magic)?;
+        file.write_all(&[VERSION, signature.len() as u8])?;
+        file.write_all(signature.as_bytes())?;
+        file.write_all(&self.plinths.to_le_bytes())?;
+        file.write_all(values)?;
+        file.flush()
+    }
+
+    /// Reads a table from its `.s6w` file and the `.s6m` file next to it.
+    pub fn read<T: AsRef<Path>>(wdl_path: T) -> io::Result<Self> {
+        let wdl_path = wdl_path.as_ref();
+        let (signature, plinths, wdl) = read_file(wdl_path, WDL_MAGIC)?;
+        let (_, _, dtm) = read_file(&wdl_path.with_extension("s6m"), DTM_MAGIC)?;
+        if wdl.len() != signature.size() || dtm.len() != wdl.len() {
+            return Err(invalid_data("bad tablebase size"));
+        }
+        Ok(Self {
+            signature,
+            plinths,
+            wdl,
+            dtm,
+        })
+    }
+}
+
+/// File layout: 4 magic bytes, format version, signature length and text,
+/// plinth mask as little-endian u64, then one byte per position in index
+/// order.
+fn read_file(path: &Path, magic: &[u8; 4]) -> io::Result<(Signature, u64, Vec<u8>)> {
+    let data = fs::read(path)?;
+    if data.len() < 6 || &data[..4] != magic {
+        return Err(invalid_data("bad tablebase header"));
+    }
+    if data[4] != VERSION {
+        return Err(invalid_data("unsupported tablebase version"));
+    }
+    let end = 6 + data[5] as usize;
+    let signature = data
+        .get(6..end)
+        .and_then(|s| std::str::from_utf8(s).ok())
+        .and_then(|s| s.parse().ok())
+        .ok_or_else(|| invalid_data("bad tablebase signature"))?;
+    let plinths = data
+        .get(end..end + 8)
+        .and_then(|b| b.try_into().ok())
+        .map(u64::from_le_bytes)
+        .ok_or_else(|| invalid_data("bad tablebase header"))?;
+    Ok((signature, plinths, data[end + 8..].to_vec()))
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Position index: side to move, then one base-36 digit per piece.
+fn encode(stm: Color, squares: impl Iterator<Item = u8>) -> usize {
+    let mut index = (stm == Color::Black) as usize;
+    for sq in squares {
+        index = index * SQUARES + sq as usize;
+    }
+    index
+}
+
+fn decode(mut index: usize, pieces: usize) -> (Color, [u8; MAX_PIECES]) {
+    let mut squares = [NONE; MAX_PIECES];
+    for sq in squares[..pieces].iter_mut().rev() {
+        *sq = (index % SQUARES) as u8;
+        index /= SQUARES;
+    }
+    let stm = if index == 0 {
+        Color::White
+    } else {
+        Color::Black
+    };
+    (stm, squares)
+}
+
+/// Same attacks as the crate's `get_moves` for non-pawn pieces.
+fn attacks(piece_type: PieceType, sq: u8, blockers: u64) -> u64 {
+    let square = Square6::from_index(sq).unwrap();
+    let blockers = BB6::new(blockers);
+    let knight =
+        || Attacks6::get_non_sliding_attacks(PieceType::Knight, &square, Color::White, blockers);
+    let bb = match piece_type {
+        PieceType::King | PieceType::Knight => {
+            Attacks6::get_non_sliding_attacks(piece_type, &square, Color::White, blockers)
+        }
+        PieceType::Giraffe => Attacks6::get_giraffe_attacks(&square),
+        PieceType::Chancellor => {
+            knight() | &Attacks6::get_sliding_attacks(PieceType::Rook, &square, blockers)
+        }
+        PieceType::ArchBishop => {
+            knight() | &Attacks6::get_sliding_attacks(PieceType::Bishop, &square, blockers)
+        }
+        _ => Attacks6::get_sliding_attacks(piece_type, &square, blockers),
+    };
+    // The crate's Giraffe on b3 also reaches bit 36, past the board.
+    bb.0 & BOARD
+}
+
+/// Move rules for one table under construction.
+struct Builder<'a> {
+    pieces: Vec<(PieceType, Color)>,
+    plinths: u64,
+    /// Table reached by capturing each piece, `None` for kings.
+    captures: Vec<Option<&'a Table>>,
+}
+
+impl Builder<'_> {
+    fn occupied(&self, squares: &[u8]) -> u64 {
+        squares[..self.pieces.len()]
+            .iter()
+            .filter(|sq| **sq != NONE)
+            .fold(0, |bb, sq| bb | 1 << sq)
+    }
+
+    fn king(&self, squares: &[u8], color: Color) -> u8 {
+        let index = self
+            .pieces
+            .iter()
+            .position(|p| *p == (PieceType::King, color));
+        squares[index.unwrap()]
+    }
+
+    fn attacked(&self, squares: &[u8], target: u8, by: Color) -> bool {
+        let blockers = self.occupied(squares) | self.plinths;
+        self.pieces.iter().zip(squares).any(|(&(pt, color), &sq)| {
+            color == by && sq != NONE && attacks(pt, sq, blockers) & 1 << target != 0
+        })
+    }
+
+    /// Distinct squares, no king or slider on a plinth and the side that
+    /// just moved not in check.
+    fn valid(&self, stm: Color, squares: &[u8]) -> bool {
+        let mut occupied = 0u64;
+        for (&(pt, _), &sq) in self.pieces.iter().zip(squares) {
+            let bit = 1 << sq;
+            if occupied & bit != 0 || (self.plinths & bit != 0 && !JUMPERS.contains(&pt)) {
+                return false;
+            }
+            occupied |= bit;
+        }
+        !self.attacked(squares, self.king(squares, stm.flip()), stm)
+    }
+
+    /// Visits the WDL code and DTM of the position after every legal move
+    /// until `visit` returns false. Returns the number of moves visited.
+    fn children(
+        &self,
+        stm: Color,
+        squares: &[u8],
+        wdl: &[u8],
+        dtm: &[u8],
+        mut visit: impl FnMut(u8, u8) -> bool,
+    ) -> usize {
+        let n = self.pieces.len();
+        let blockers = self.occupied(squares) | self.plinths;
+        let own = self.pieces[..n]
+            .iter()
+            .zip(squares)
+            .filter(|((_, color), _)| *color == stm)
+            .fold(0u64, |bb, (_, sq)| bb | 1 << sq);
+        let mut count = 0;
+        for (i, &(pt, color)) in self.pieces.iter().enumerate() {
+            if color != stm {
+                continue;
+            }
+            let mut targets = attacks(pt, squares[i], blockers) & !own;
+            if !JUMPERS.contains(&pt) {
+                targets &= !self.plinths;
+            }
+            while targets != 0 {
+                let to = targets.trailing_zeros() as u8;
+                targets &= targets - 1;
+                let mut next = [NONE; MAX_PIECES];
+                next[..n].copy_from_slice(&squares[..n]);
+                next[i] = to;
+                let captured = (0..n).find(|&j| j != i && squares[j] == to);
+                if let Some(j) = captured {
+                    next[j] = NONE;
+                }
+                if self.attacked(&next, self.king(&next, stm), stm.flip()) {
+                    continue;
+                }
+                count += 1;
+                let remaining = next[..n].iter().copied().filter(|sq| *sq != NONE);
+                let index = encode(stm.flip(), remaining);
+                let (code, plies) = match captured.and_then(|j| self.captures[j]) {
+                    Some(table) => (table.wdl[index], table.dtm[index]),
+                    None => (wdl[index], dtm[index]),
+                };
+                if !visit(code, plies) {
+                    return count;
+                }
+            }
+        }
+        count
+    }
+
+    fn build(&self, signature: Signature) -> Table {
+        let size = signature.size();
+        let n = self.pieces.len();
+        let mut wdl = vec![UNRESOLVED; size];
+        let mut dtm = vec![0; size];
+
+        let all: Vec<u32> = (0..size as u32).collect();
+        let terminal = parallel(&all, |index| {
+            let (stm, squares) = decode(index, n);
+            if !self.valid(stm, &squares[..n]) {
+                return Some((INVALID, 0));
+            }
+            if self.children(stm, &squares, &wdl, &dtm, |_, _| false) > 0 {
+                return None;
+            }
+            let mated = self.attacked(&squares, self.king(&squares, stm), stm.flip());
+            Some((if mated { LOSS } else { DRAW }, 0))
+        });
+        drop(all);
+        apply(&mut wdl, &mut dtm, &terminal);
+
+        // Sub-tables can hold losses longer than anything found here yet.
+        let horizon = self.captures.iter().flatten().map(|t| t.max_dtm()).max();
+        let horizon = horizon.unwrap_or(0) as usize + 1;
+        let mut pending: Vec<u32> = (0..size as u32)
+            .filter(|i| wdl[*i as usize] == UNRESOLVED)
+            .collect();
+        let mut idle = 0;
+        let mut ply = 1;
+        while !pending.is_empty() && (ply <= horizon || idle < 2) && ply < u8::MAX as usize {
+            let updates = parallel(&pending, |index| {
+                let (stm, squares) = decode(index, n);
+                if ply % 2 == 1 {
+                    let mut win = false;
+                    self.children(stm, &squares, &wdl, &dtm, |code, plies| {
+                        win = code == LOSS && plies as usize == ply - 1;
+                        !win
+                    });
+                    win.then_some((WIN, ply as u8))
+                } else {
+                    let mut lost = true;
+                    self.children(stm, &squares, &wdl, &dtm, |code, plies| {
+                        lost = code == WIN && (plies as usize) < ply;
+                        lost
+                    });
+                    lost.then_some((LOSS, ply as u8))
+                }
+            });
+            idle = if updates.is_empty() { idle + 1 } else { 0 };
+            apply(&mut wdl, &mut dtm, &updates);
+            pending.retain(|i| wdl[*i as usize] == UNRESOLVED);
+            ply += 1;
+        }
+        for code in wdl.iter_mut().filter(|code| **code == UNRESOLVED) {
+            *code = DRAW;
+        }
+        Table {
+            signature,
+            plinths: self.plinths,
+            wdl,
+            dtm,
+        }
+    }
+}
+
+/// Runs `f` over `indices` on all cores, collecting the new values.
+fn parallel(indices: &[u32], f: impl Fn(usize) -> Option<(u8, u8)> + Sync) -> Vec<(u32, u8, u8)> {
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+    let chunk = indices.len().div_ceil(threads).max(1);
+    thread::scope(|scope| {
+        let handles: Vec<_> = indices
+            .chunks(chunk)
+            .map(|chunk| {
+                let f = &f;
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .filter_map(|i| f(*i as usize).map(|(code, plies)| (*i, code, plies)))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect()
+    })
+}
+
+fn apply(wdl: &mut [u8], dtm: &mut [u8], updates: &[(u32, u8, u8)]) {
+    for &(index, code, plies) in updates {
+        wdl[index as usize] = code;
+        dtm[index as usize] = plies;
+    }
+}
+
+/// Retrograde generator for one plinth layout. Captures lead into smaller
+/// tables, which are generated first and kept.
+pub struct Generator {
+    plinths: u64,
+    tables: HashMap<String, Table>,
+}
+
+impl Generator {
+    pub fn new(plinths: BB6<Square6>) -> Self {
+        Self {
+            plinths: plinths.0,
+            tables: HashMap::new(),
+        }
+    }
+
+    /// Generates `signature` and every table its captures reach.
+    pub fn generate(&mut self, signature: &Signature) -> &Table {
+        let key = signature.to_string();
+        if !self.tables.contains_key(&key) {
+            let pieces = signature.pieces();
+            let smaller: Vec<Option<Signature>> =
+                (0..pieces.len()).map(|i| signature.without(i)).collect();
+            for sub in smaller.iter().flatten() {
+                self.generate(sub);
+            }
+            let builder = Builder {
+                pieces,
+                plinths: self.plinths,
+                captures: smaller
+                    .iter()
+                    .map(|sub| sub.as_ref().map(|sub| &self.tables[&sub.to_string()]))
+                    .collect(),
+            };
+            let table = builder.build(signature.clone());
+            self.tables.insert(key.clone(), table);
+        }
+        &self.tables[&key]
+    }
+
+    pub fn tables(&self) -> impl Iterator<Item = &Table> {
+        self.tables.values()
+    }
+
+    /// Writes every generated table into `dir`, returns how many.
+    pub fn write<T: AsRef<Path>>(&self, dir: T) -> io::Result<usize> {
+        fs::create_dir_all(&dir)?;
+        for table in self.tables() {
+            table.write(&dir)?;
+        }
+        Ok(self.tables.len())
+    }
+}
+
+/// Loaded tables, probed by material and plinth layout.
+#[derive(Default)]
+pub struct Tablebase {
+    tables: HashMap<(String, u64), Table>,
+}
+
+impl Tablebase {
+    /// Loads every `.s6w`/`.s6m` pair in `dir`.
+    pub fn load<T: AsRef<Path>>(dir: T) -> io::Result<Self> {
+        let mut tablebase = Self::default();
+        for entry in fs::read_dir(dir)? {
+            let path: PathBuf = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "s6w") {
+                tablebase.insert(Table::read(&path)?);
+            }
+        }
+        Ok(tablebase)
+    }
+
+    pub fn insert(&mut self, table: Table) {
+        let key = (table.signature.to_string(), table.plinths);
+        self.tables.insert(key, table);
+    }
+
+    pub fn len(&self) -> usize {
+        self.tables.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tables.is_empty()
+    }
+
+    /// Result for the side to move, if the position's material and plinths
+    /// have a loaded table.
+    pub fn probe(&self, position: &P6<Square6, BB6<Square6>>) -> Option<Probe> {
+        if self.tables.is_empty() {
+            return None;
+        }
+        let pieces = position.player_bb(Color::White) | &position.player_bb(Color::Black);
+        if pieces.len() as usize > MAX_PIECES {
+            return None;
+        }
+        let mut squares = vec![];
+        let mut sides = [vec![], vec![]];
+        for (side, color) in [Color::White, Color::Black].into_iter().enumerate() {
+            squares.push(position.find_king(color)?.index() as u8);
+            for pt in PIECE_ORDER {
+                for sq in position.player_bb(color) & &position.type_bb(&pt) {
+                    sides[side].push(pt);
+                    squares.push(sq.index() as u8);
+                }
+            }
+        }
+        // Pawns have no tables.
+        if squares.len() != pieces.len() as usize {
+            return None;
+        }
+        let key = (
+            Signature { sides }.to_string(),
+            position.player_bb(Color::NoColor).0,
+        );
+        self.tables
+            .get(&key)?
+            .probe(position.side_to_move(), &squares)
+    }
+}
@@ -0,0 +1,190 @@
+use shuuro::{
+    Move, Square, Variant,
+    attacks::Attacks,
+    bitboard::BitBoard,
+    position::{Board, Placement, Play, Rules, Sfen},
+};
+use std::{
+    fmt::Display,
+    fs,
+    hash::Hash,
+    io,
+    path::Path,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    engine::{Engine, EngineDefs, SearchLimits},
+    game_record::VARIANTS,
+};
+
+// EPD-like format, one position per line:
+//
+//   <sfen> bm <move> [<move> ...]; am <move> [...]; id "<name>"; variant <name>;
+//
+// The sfen has its usual four fields. `bm` lists the moves that solve the
+// position, `am` the ones that fail it; at least one of them is required.
+// `variant` is needed for fairy pieces, which the crate's rules ignore
+// otherwise. Other operations are skipped, blank lines and `#` comments too.
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EpdEntry {
+    pub sfen: String,
+    pub best_moves: Vec<String>,
+    pub avoid_moves: Vec<String>,
+    pub id: String,
+    pub variant: Option<Variant>,
+}
+
+impl EpdEntry {
+    /// Whether playing `mv` solves the position.
+    pub fn accepts(&self, mv: &str) -> bool {
+        (self.best_moves.is_empty() || self.best_moves.iter().any(|m| m == mv))
+            && !self.avoid_moves.iter().any(|m| m == mv)
+    }
+
+    pub fn load<T: AsRef<Path>>(path: T) -> io::Result<Vec<Self>> {
+        fs::read_to_string(path)?
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index, line.split('#').next().unwrap_or_default().trim()))
+            .filter(|(_, line)| !line.is_empty())
+            .map(|(index, line)| {
+                line.parse().map_err(|_| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("bad epd line {}", index + 1),
+                    )
+                })
+            })
+            .collect()
+    }
+}
+
+impl FromStr for EpdEntry {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut tokens = s.split_whitespace();
+        let fields: Vec<&str> = tokens.by_ref().take(4).collect();
+        if fields.len() != 4 {
+            return Err(());
+        }
+        let mut entry = Self {
+            sfen: fields.join(" "),
+            best_moves: vec![],
+            avoid_moves: vec![],
+            id: String::new(),
+            variant: None,
+        };
+        let operations = tokens.collect::<Vec<_>>().join(" ");
+        for operation in operations.split(';').map(str::trim) {
+            let (opcode, operands) = operation.split_once(' ').unwrap_or((operation, ""));
+            let moves = || operands.split_whitespace().map(String::from);
+            match opcode {
+                "bm" => entry.best_moves.extend(moves()),
+                "am" => entry.avoid_moves.extend(moves()),
+                "id" => entry.id = operands.trim().trim_matches('"').to_string(),
+                "variant" => {
+                    let name = operands.trim();
+                    let variant = VARIANTS.into_iter().find(|v| v.to_string() == name);
+                    entry.variant = Some(variant.ok_or(())?);
+                }
+                _ => (),
+            }
+        }
+        if entry.best_moves.is_empty() && entry.avoid_moves.is_empty() {
+            return Err(());
+        }
+        Ok(entry)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SuiteResult {
+    pub id: String,
+    /// Move of the last finished iteration, `None` for a bad sfen.
+    pub best_move: Option<String>,
+    /// Time after which the engine kept a solving move, `None` when it
+    /// ended on a wrong one.
+    pub solved_in: Option<Duration>,
+    pub depth: i32,
+}
+
+/// Searches every position of `entries` with `limits`, one iteration at a
+/// time so the moment the solution was found is known. Node limits count
+/// per iteration.
+pub fn run<E, S, B, A, P, D, const FILE: usize, const BITBOARD_SIZE: usize, const RANK: usize>(
+    engine: &mut E,
+    entries: &[EpdEntry],
+    limits: &SearchLimits,
+) -> Vec<SuiteResult>
+where
+    E: Engine<S, B, A, P, D, FILE, BITBOARD_SIZE, RANK>,
+    S: Square + Hash + Send + 'static,
+    B: BitBoard<S>,
+    A: Attacks<S, B>,
+    P: Sized
+        + Display
+        + Clone
+        + Board<S, B, A>
+        + Sfen<S, B, A>
+        + Placement<S, B, A>
+        + Play<S, B, A>
+        + Rules<S, B, A>
+        + Send
+        + 'static,
+    D: EngineDefs<S, B, FILE>,
+{
+    entries
+        .iter()
+        .map(|entry| {
+            let mut result = SuiteResult {
+                id: entry.id.clone(),
+                best_move: None,
+                solved_in: None,
+                depth: 0,
+            };
+            let mut position = P::new();
+            if let Some(variant) = entry.variant {
+                position.update_variant(variant);
+            }
+            if position.set_sfen(&entry.sfen).is_err() {
+                println!("info string {} has a bad sfen", entry.id);
+                return result;
+            }
+            let start = Instant::now();
+            for depth in 1..=limits.max_depth() {
+                let movetime = match limits.movetime {
+                    Some(movetime) if start.elapsed() >= movetime => break,
+                    Some(movetime) => Some(movetime - start.elapsed()),
+                    None => None,
+                };
+                let iteration = SearchLimits {
+                    depth: Some(depth),
+                    nodes: limits.nodes,
+                    movetime,
+                };
+                let searched = engine.search(&position, iteration);
+                let cut = searched.depth < depth;
+                if cut && result.best_move.is_some() {
+                    break;
+                }
+                let best_move = searched.best_move.as_ref().map(Move::to_string);
+                match &best_move {
+                    Some(mv) if entry.accepts(mv) => {
+                        result.solved_in.get_or_insert(start.elapsed());
+                    }
+                    _ => result.solved_in = None,
+                }
+                result.best_move = best_move;
+                result.depth = searched.depth;
+                if cut {
+                    break;
+                }
+            }
+            result
+        })
+        .collect()
+}
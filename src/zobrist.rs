@@ -0,0 +1,89 @@
+use shuuro::{
+    Color, Piece, PieceType, Square, attacks::Attacks, bitboard::BitBoard, position::Board,
+};
+use std::hash::Hash;
+
+use crate::prng::Prng;
+
+// 9 piece types for both colours, then plinths. The keys only depend on the
+// square index, so the same table serves 6x6, 8x8 and 12x12 boards.
+const KINDS: usize = 19;
+const MAX_SQUARES: usize = 144;
+
+const fn generate_keys() -> ([[u64; MAX_SQUARES]; KINDS], u64) {
+    let mut keys = [[0; MAX_SQUARES]; KINDS];
+    let mut prng = Prng::new(0x5348_5555_524f);
+    let mut kind = 0;
+    while kind < KINDS {
+        let mut sq = 0;
+        while sq < MAX_SQUARES {
+            keys[kind][sq] = prng.next_u64();
+            sq += 1;
+        }
+        kind += 1;
+    }
+    (keys, prng.next_u64())
+}
+
+const KEYS: ([[u64; MAX_SQUARES]; KINDS], u64) = generate_keys();
+
+pub fn piece_key(piece: Piece, sq: usize) -> u64 {
+    let kind = match piece.piece_type {
+        PieceType::Plinth => KINDS - 1,
+        pt => pt.index() + 9 * piece.color.index(),
+    };
+    KEYS.0[kind][sq]
+}
+
+pub fn side_key() -> u64 {
+    KEYS.1
+}
+
+/// Hash of both sides' pawns, used by the pawn table.
+pub fn pawn_hash<S, B, A, P>(position: &P) -> u64
+where
+    S: Square + Hash,
+    B: BitBoard<S>,
+    A: Attacks<S, B>,
+    P: Board<S, B, A>,
+{
+    let mut key = 0;
+    for color in [Color::White, Color::Black] {
+        let piece = Piece {
+            piece_type: PieceType::Pawn,
+            color,
+        };
+        for sq in position.player_bb(color) & &position.type_bb(&PieceType::Pawn) {
+            key ^= piece_key(piece, sq.index());
+        }
+    }
+    key
+}
+
+/// Hash of pieces, plinths and side to move.
+pub fn hash<S, B, A, P>(position: &P) -> u64
+where
+    S: Square + Hash,
+    B: BitBoard<S>,
+    A: Attacks<S, B>,
+    P: Board<S, B, A>,
+{
+    let mut key = 0;
+    let pieces = position.player_bb(Color::White) | &position.player_bb(Color::Black);
+    for sq in pieces {
+        if let Some(piece) = *position.piece_at(sq) {
+            key ^= piece_key(piece, sq.index());
+        }
+    }
+    let plinth = Piece {
+        piece_type: PieceType::Plinth,
+        color: Color::NoColor,
+    };
+    for sq in position.player_bb(Color::NoColor) {
+        key ^= piece_key(plinth, sq.index());
+    }
+    if position.side_to_move() == Color::Black {
+        key ^= side_key();
+    }
+    key
+}
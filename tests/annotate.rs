@@ -0,0 +1,37 @@
+use shuuro::Variant;
+use shuuro_engine::{
+    Engine,
+    annotate::{self, AnnotateOptions},
+    engine8::search::Engine8,
+    game_record::{Annotation, FightMove, GameRecord, GameResult},
+    on_board,
+};
+
+#[test]
+fn hanging_the_queen_is_a_blunder() {
+    let mut game = GameRecord::new(Variant::Standard, "r3k3/8/8/8/8/8/8/3QK3 w - 1");
+    game.fight_moves = ["d1_d7", "e8_d7"]
+        .map(|mv| FightMove::new(mv.to_string()))
+        .to_vec();
+    game.result = GameResult::BlackWins;
+
+    Engine8::init();
+    let mut engine = Engine8::new();
+    let options = AnnotateOptions::default();
+    let annotated = on_board!(8, annotate::annotate, &mut engine, &options, game).unwrap();
+
+    let [blunder, capture] = &annotated.fight_moves[..] else {
+        panic!("{annotated}");
+    };
+    assert_eq!(blunder.annotation, Some(Annotation::Blunder), "{annotated}");
+    assert!(!blunder.variation.is_empty(), "{annotated}");
+    assert_ne!(blunder.variation[0], "d1_d7");
+    assert_eq!(capture.annotation, None, "{annotated}");
+    assert!(capture.eval.unwrap() < -300, "{annotated}");
+
+    let text = format!("{annotated}\n{annotated}");
+    assert_eq!(
+        GameRecord::parse_all(&text),
+        Ok(vec![annotated.clone(), annotated])
+    );
+}
@@ -0,0 +1,120 @@
+use shuuro::{Color, PieceType, Variant};
+use shuuro_engine::{
+    army::{Army, ArmyEvaluator},
+    engine::{EngineDefs, SearchLimits, with_stack},
+    engine8::search::Defs8,
+};
+
+fn army(s: &str) -> Army {
+    s.parse().unwrap()
+}
+
+#[test]
+fn parses_pieces_in_either_case() {
+    let parsed = army("qRRbbpppp");
+    assert_eq!(parsed.count(PieceType::King), 1);
+    assert_eq!(parsed.count(PieceType::Queen), 1);
+    assert_eq!(parsed.count(PieceType::Rook), 2);
+    assert_eq!(parsed.count(PieceType::Bishop), 2);
+    assert_eq!(parsed.count(PieceType::Pawn), 4);
+    assert_eq!(parsed.count(PieceType::Knight), 0);
+    assert_eq!(army("AA").count(PieceType::ArchBishop), 2);
+    // The king is always there, once.
+    assert_eq!(army("kk"), army(""));
+    assert!("qx".parse::<Army>().is_err());
+}
+
+#[test]
+fn hand_lists_pieces_in_type_order() {
+    let parsed = army("pnqrpg");
+    assert_eq!(parsed.to_hand(Color::White), "KQRNPPG");
+    assert_eq!(parsed.to_hand(Color::Black), "kqrnppg");
+    assert_eq!(parsed.to_string(), "KQRNPPG");
+    assert_eq!(army(&parsed.to_string()), parsed);
+}
+
+#[test]
+fn static_score_is_symmetric() {
+    for variant in [
+        Variant::ShuuroMini,
+        Variant::StandardFairy,
+        Variant::ShuuroFairy,
+    ] {
+        let evaluator = ArmyEvaluator::new(variant);
+        for (first, second) in [("qrr", "cbn"), ("pppp", "r"), ("aa", "qr")] {
+            let (first, second) = (army(first), army(second));
+            let score = evaluator.static_score(&first, &second);
+            let reverse = evaluator.static_score(&second, &first);
+            assert!(
+                (score + reverse - 1.0).abs() < 1e-9,
+                "{variant:?} {first} {second}"
+            );
+            assert_eq!(evaluator.static_score(&first, &first), 0.5);
+        }
+    }
+}
+
+#[test]
+fn material_follows_the_search_phase_bounds() {
+    let expected = |diff: i32| 1.0 / (1.0 + 10f64.powf(-diff as f64 / 400.0));
+    let evaluator = ArmyEvaluator::new(Variant::Standard);
+    let (min, max) = Defs8::midgame_min();
+
+    // Phase 7 is an endgame.
+    assert!(7 <= min);
+    let endgame = Defs8::get_endgame_piece_value;
+    let diff = endgame(PieceType::Queen) + endgame(PieceType::Knight) - endgame(PieceType::Rook);
+    assert_eq!(
+        evaluator.static_score(&army("QN"), &army("R")),
+        expected(diff)
+    );
+
+    // Phase 31 is past the cap, a full midgame.
+    assert!(31 >= max);
+    let diff = Defs8::get_piece_value(PieceType::Rook) - Defs8::get_piece_value(PieceType::Knight);
+    assert_eq!(
+        evaluator.static_score(&army("QQRRRR"), &army("QQRRRN")),
+        expected(diff)
+    );
+}
+
+#[test]
+fn simulation_is_reproducible() {
+    let limits = SearchLimits::parse("depth 1");
+    let scores = with_stack(move || {
+        let evaluator = |seed| {
+            ArmyEvaluator::new(Variant::ShuuroMiniFairy).with_simulation(2, seed, limits.clone())
+        };
+        let (first, second) = (army("qrnpp"), army("crpp"));
+        [
+            evaluator(1).expected_score(&first, &second),
+            evaluator(1).expected_score(&first, &second),
+        ]
+    });
+    assert_eq!(scores[0], scores[1]);
+    assert!((0.0..=1.0).contains(&scores[0]));
+}
+
+#[test]
+fn two_archbishops_against_queen_and_rook() {
+    let (archbishops, queen_rook) = (army("AAPPPPPP"), army("QRPPPPPP"));
+    // Phase 10 is below the 12x12 midgame threshold, so 2 x 980 against
+    // 1150 + 650 in the endgame table.
+    let score = ArmyEvaluator::new(Variant::ShuuroFairy).static_score(&archbishops, &queen_rook);
+    assert!(score > 0.5 && score < 0.8, "{score}");
+
+    let limits = SearchLimits::parse("depth 1");
+    let simulated = with_stack(move || {
+        ArmyEvaluator::new(Variant::ShuuroFairy)
+            .with_simulation(2, 0, limits)
+            .expected_score(&archbishops, &queen_rook)
+    });
+    // The static estimate counts as one game next to two played ones, each
+    // scoring 0, 0.5 or 1.
+    let played = simulated * 3.0 - score;
+    assert!((0.0..=2.0).contains(&played), "{simulated}");
+    assert!(
+        (played * 2.0 - (played * 2.0).round()).abs() < 1e-9,
+        "{simulated}"
+    );
+}
@@ -0,0 +1,114 @@
+use shuuro::{
+    Square, Variant,
+    attacks::Attacks,
+    position::{Board, Play},
+    shuuro8::{attacks8::Attacks8, bitboard8::BB8, position8::P8, square8::Square8},
+};
+use shuuro_engine::{
+    book::{self, Book, BookEntry},
+    game_record::{GameRecord, GameResult},
+    prng::Prng,
+    zobrist,
+};
+use std::fs;
+
+type Position = P8<Square8, BB8<Square8>>;
+type Attack8 = Attacks8<Square8, BB8<Square8>>;
+
+const START: &str = "4k3/8/8/8/8/8/8/R3K3 w - 1";
+
+fn entry(key: u64, from: u8, to: u8, weight: u16) -> BookEntry {
+    BookEntry {
+        key,
+        from,
+        to,
+        weight,
+        learn: 1,
+    }
+}
+
+fn game(moves: &[&str], result: GameResult) -> GameRecord {
+    let mut game = GameRecord::new(Variant::Standard, START);
+    for mv in moves {
+        game.push(mv.to_string(), START);
+    }
+    game.result = result;
+    game
+}
+
+#[test]
+fn entries_are_sorted_by_weight() {
+    let book = Book::new(
+        8,
+        vec![
+            entry(7, 0, 1, 1),
+            entry(3, 2, 3, 9),
+            entry(7, 0, 2, 5),
+            entry(7, 0, 3, 3),
+        ],
+    );
+    let weights: Vec<u16> = book.entries(7).iter().map(|e| e.weight).collect();
+    assert_eq!(weights, [5, 3, 1]);
+    assert_eq!(book.entries(3).len(), 1);
+    assert!(book.entries(5).is_empty());
+}
+
+#[test]
+fn save_and_load_round_trip() {
+    let book = Book::new(12, vec![entry(u64::MAX, 143, 0, 2), entry(1, 5, 6, 7)]);
+    let path = std::env::temp_dir().join("shuuro-engine-book.bin");
+    book.save(&path).unwrap();
+    let loaded = Book::load(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(loaded, book);
+    assert_eq!(loaded.files(), 12);
+}
+
+#[test]
+fn seeded_pick_is_deterministic_and_weighted() {
+    let book = Book::new(
+        8,
+        vec![entry(1, 0, 1, 3), entry(1, 0, 2, 1), entry(1, 0, 3, 0)],
+    );
+    let picks = |seed| {
+        let mut prng = Prng::new(seed);
+        (0..1000)
+            .map(|_| book.pick(1, &mut prng).unwrap().to)
+            .collect::<Vec<_>>()
+    };
+    let first = picks(42);
+    assert_eq!(first, picks(42));
+    assert!(!first.contains(&3));
+    let heavy = first.iter().filter(|to| **to == 1).count();
+    assert!((650..850).contains(&heavy), "{heavy}");
+    assert!(book.pick(2, &mut Prng::new(0)).is_none());
+}
+
+#[test]
+fn make_skips_rare_moves() {
+    Attack8::init();
+    let games = [
+        game(&["a1_a7", "e8_d8"], GameResult::WhiteWins),
+        game(&["a1_a7", "e8_f8"], GameResult::Draw),
+        game(&["e1_d1"], GameResult::BlackWins),
+    ];
+    let book = book::make::<Square8, BB8<Square8>, Attack8, Position>(&games, 16, 2);
+
+    let mut position = Position::new();
+    position.set_sfen(START).unwrap();
+    let key = zobrist::hash::<_, _, Attack8, _>(&position);
+    let a1 = Square8::from_sfen("a1").unwrap().index() as u8;
+    let a7 = Square8::from_sfen("a7").unwrap().index() as u8;
+    assert_eq!(book.len(), 1);
+    assert_eq!(
+        book.entries(key),
+        [BookEntry {
+            key,
+            from: a1,
+            to: a7,
+            weight: 3,
+            learn: 2,
+        }]
+    );
+    assert_eq!(book.files(), 8);
+}
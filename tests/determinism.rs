@@ -0,0 +1,19 @@
+use shuuro::Variant;
+use shuuro_engine::{autoplay::play_full_game, bench, engine::SearchLimits};
+
+#[test]
+fn games_replay_bit_for_bit() {
+    for variant in [Variant::ShuuroMiniFairy, Variant::Standard] {
+        let first = play_full_game(variant, 3, SearchLimits::parse("nodes 200"));
+        let second = play_full_game(variant, 3, SearchLimits::parse("nodes 200"));
+        assert_eq!(first.to_string(), second.to_string(), "{variant:?}");
+    }
+}
+
+#[test]
+fn bench_signature_is_stable() {
+    let first = bench::run_all(2);
+    let second = bench::run_all(2);
+    assert_eq!(first.nodes, second.nodes);
+    assert_eq!(first.signature, second.signature);
+}
@@ -0,0 +1,131 @@
+use shuuro::{
+    Color, PieceType,
+    position::{Board, Outcome, Play, Sfen},
+    shuuro12::{bitboard12::BB12, position12::P12, square12::Square12},
+};
+use shuuro_engine::{
+    Engine,
+    endgame::{Endgame, SCALE_NORMAL, classify, scale_factor},
+    engine::SearchLimits,
+    engine12::search::Engine12,
+};
+
+// K Q R B N P C A G
+const KING: [u32; 9] = [1, 0, 0, 0, 0, 0, 0, 0, 0];
+
+fn with(mut counts: [u32; 9], piece_type: PieceType, count: u32) -> [u32; 9] {
+    counts[piece_type.index()] += count;
+    counts
+}
+
+fn value(piece_type: PieceType) -> i32 {
+    match piece_type {
+        PieceType::Queen => 900,
+        PieceType::Rook => 500,
+        PieceType::Bishop => 330,
+        PieceType::Knight => 320,
+        PieceType::Chancellor => 850,
+        PieceType::ArchBishop => 800,
+        PieceType::Giraffe => 300,
+        _ => 100,
+    }
+}
+
+#[test]
+fn classifies_mates_against_a_bare_king() {
+    for piece_type in [
+        PieceType::Rook,
+        PieceType::Queen,
+        PieceType::Chancellor,
+        PieceType::ArchBishop,
+    ] {
+        let strong = with(KING, piece_type, 1);
+        assert_eq!(
+            classify(&[strong, KING]),
+            Some(Endgame::Mate(Color::White)),
+            "{piece_type:?}"
+        );
+        assert_eq!(
+            classify(&[KING, strong]),
+            Some(Endgame::Mate(Color::Black)),
+            "{piece_type:?}"
+        );
+    }
+    // A single pawn left to the defender is not a bare king.
+    let rook = with(KING, PieceType::Rook, 1);
+    assert_eq!(classify(&[rook, with(KING, PieceType::Pawn, 1)]), None);
+}
+
+#[test]
+fn lone_minor_pieces_are_drawn() {
+    assert_eq!(classify(&[KING, KING]), Some(Endgame::Draw));
+    for piece_type in [PieceType::Bishop, PieceType::Knight, PieceType::Giraffe] {
+        let minor = with(KING, piece_type, 1);
+        assert_eq!(
+            classify(&[minor, KING]),
+            Some(Endgame::Draw),
+            "{piece_type:?}"
+        );
+        assert_eq!(
+            classify(&[minor, minor]),
+            Some(Endgame::Draw),
+            "{piece_type:?}"
+        );
+    }
+    let knights = with(KING, PieceType::Knight, 2);
+    assert_eq!(classify(&[knights, KING]), None);
+    assert_eq!(classify(&[with(KING, PieceType::Pawn, 1), KING]), None);
+}
+
+#[test]
+fn scale_factor_of_drawish_material() {
+    let scale = |us: [u32; 9], them: [u32; 9], opposite_bishops: bool| {
+        scale_factor(&[us, them], Color::White, opposite_bishops, value)
+    };
+    let knights = with(KING, PieceType::Knight, 2);
+    let rook = with(KING, PieceType::Rook, 1);
+    let bishop = with(KING, PieceType::Bishop, 1);
+    let queen = with(KING, PieceType::Queen, 1);
+    assert_eq!(scale(knights, KING, false), 2);
+    assert_eq!(scale(rook, bishop, false), 8);
+    assert_eq!(scale(queen, KING, false), SCALE_NORMAL);
+
+    let bishop_pawns = with(bishop, PieceType::Pawn, 2);
+    let bishop_pawn = with(bishop, PieceType::Pawn, 1);
+    assert_eq!(scale(bishop_pawns, bishop_pawn, true), 32);
+    assert_eq!(scale(bishop_pawns, bishop_pawn, false), SCALE_NORMAL);
+
+    // Black ahead reads its own counts.
+    assert_eq!(
+        scale_factor(&[KING, knights], Color::Black, false, value),
+        2
+    );
+}
+
+#[test]
+fn converts_rook_against_king_on_12x12() {
+    const MAX_PLIES: usize = 60;
+    Engine12::init();
+    let mut engine = Engine12::new();
+    let mut position: P12<Square12, BB12<Square12>> = P12::new();
+    position
+        .set_sfen("12/12/12/12/12/12/5k6/12/12/12/12/R5K5 w - 1")
+        .unwrap();
+
+    for ply in 0..MAX_PLIES {
+        let result = engine.search(&position, SearchLimits::parse("depth 4"));
+        let best_move = result.best_move.expect("the rook side always has a move");
+        match position.make_move(best_move) {
+            Ok(Outcome::Checkmate { color }) => {
+                assert_eq!(color, Color::White);
+                return;
+            }
+            Ok(_) => (),
+            Err(err) => panic!("drawn after {ply} plies: {err:?}"),
+        }
+    }
+    panic!(
+        "no mate within {MAX_PLIES} plies: {}",
+        position.generate_sfen()
+    );
+}
@@ -0,0 +1,61 @@
+use shuuro::{
+    Variant,
+    position::{Board, Play},
+    shuuro8::{bitboard8::BB8, position8::P8, square8::Square8},
+};
+use shuuro_engine::{
+    Engine, EngineDefs,
+    engine::{FairyTerm, PlinthTerm},
+    engine8::search::{Defs8, Engine8},
+};
+
+fn position(sfen: &str) -> P8<Square8, BB8<Square8>> {
+    let mut position = P8::new();
+    position.update_variant(Variant::StandardFairy);
+    position.set_sfen(sfen).unwrap();
+    position
+}
+
+fn fairy(sfen: &str) -> i32 {
+    Engine8::init();
+    Engine8::new().fairy_evaluation(&position(sfen))
+}
+
+#[test]
+fn chancellor_prefers_open_files() {
+    // Only the pawn in front of the Chancellor differs.
+    let open = fairy("4k3/pp3ppp/8/8/8/8/PP3PPP/3CK3 w - 1");
+    let half_open = fairy("4k3/pp1p1ppp/8/8/8/8/PP3PPP/3CK3 w - 1");
+    let closed = fairy("4k3/pp1p1ppp/8/8/8/8/PP1P1PPP/3CK3 w - 1");
+    assert_eq!(
+        open - closed,
+        Defs8::fairy_weight(FairyTerm::ChancellorOpenFile)
+    );
+    assert_eq!(
+        half_open - closed,
+        Defs8::fairy_weight(FairyTerm::ChancellorHalfOpenFile)
+    );
+}
+
+#[test]
+fn trapped_giraffe_is_penalised() {
+    // From a1 the Giraffe only leaps to b5 and e2, from d4 to c8, e8, h3
+    // and h5.
+    let free = fairy("k7/8/8/1P6/3G4/8/4P3/4K3 w - 1");
+    let cornered = fairy("k7/8/8/1P6/8/8/8/G3K3 w - 1");
+    let trapped = fairy("k7/8/8/1P6/8/8/4P3/G3K3 w - 1");
+    assert_eq!(free, 0);
+    assert_eq!(cornered, -Defs8::fairy_weight(FairyTerm::GiraffeCornered));
+    assert_eq!(trapped, -Defs8::fairy_weight(FairyTerm::GiraffeTrapped));
+}
+
+#[test]
+fn plinth_blocks_slider() {
+    let plinth = |sfen| Engine8::new().plinth_evaluation(&position(sfen));
+    Engine8::init();
+    // The plinth sits on the rook's file or off all its rays.
+    let blocked = plinth("4k3/8/8/8/_.7/8/8/R3K3 w - 1");
+    let free = plinth("4k3/8/8/7_./8/8/8/R3K3 w - 1");
+    assert_eq!(free, 0);
+    assert_eq!(blocked, -Defs8::plinth_weight(PlinthTerm::BlockedSlider));
+}
@@ -0,0 +1,32 @@
+use shuuro::Variant;
+use shuuro_engine::{
+    autoplay::{GameResult, play_full_game},
+    engine::SearchLimits,
+};
+
+const VARIANTS: [Variant; 6] = [
+    Variant::Shuuro,
+    Variant::ShuuroFairy,
+    Variant::Standard,
+    Variant::StandardFairy,
+    Variant::ShuuroMini,
+    Variant::ShuuroMiniFairy,
+];
+
+#[test]
+fn every_variant_plays_to_the_end() {
+    for variant in VARIANTS {
+        let record = play_full_game(variant, 7, SearchLimits::parse("nodes 200"));
+        assert!(!record.shop_moves.is_empty(), "{variant:?}: no purchases");
+        assert!(!record.deploy_moves.is_empty(), "{variant:?}: no drops");
+        assert!(
+            !record.fight_moves.is_empty(),
+            "{variant:?}: no fight moves"
+        );
+        assert_ne!(
+            record.result,
+            GameResult::Aborted,
+            "{variant:?}: {record:?}"
+        );
+    }
+}
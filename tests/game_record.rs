@@ -0,0 +1,108 @@
+use shuuro::{
+    Variant,
+    attacks::Attacks,
+    shuuro8::{attacks8::Attacks8, bitboard8::BB8, position8::P8, square8::Square8},
+};
+use shuuro_engine::game_record::{Annotation, FightMove, GameRecord, GameResult, RecordError};
+
+type Position = P8<Square8, BB8<Square8>>;
+type Attack8 = Attacks8<Square8, BB8<Square8>>;
+
+fn replay(record: &mut GameRecord) -> Result<Position, RecordError> {
+    Attack8::init();
+    record.replay::<Square8, BB8<Square8>, Attack8, Position>()
+}
+
+fn fight(sfen: &str, moves: &[&str]) -> GameRecord {
+    let mut record = GameRecord::new(Variant::Standard, sfen);
+    record.fight_moves = moves
+        .iter()
+        .map(|mv| FightMove::new(mv.to_string()))
+        .collect();
+    record
+}
+
+#[test]
+fn tags_comments_and_variations_round_trip() {
+    let record = GameRecord {
+        variant: Variant::StandardFairy,
+        seed: 7,
+        start_sfen: String::from("8/8/2_.5/8/8/5_.2/8/8 w - 1"),
+        shop_moves: vec![],
+        deploy_moves: vec![],
+        fight_sfen: String::from("4k3/8/8/8/8/8/8/R3K3 b - 1"),
+        fight_moves: vec![
+            FightMove {
+                eval: Some(-35),
+                clock: Some(3723),
+                comment: Some(String::from("Only move")),
+                ..FightMove::new(String::from("e8_d7"))
+            },
+            FightMove {
+                eval: Some(120),
+                annotation: Some(Annotation::Mistake),
+                variation: vec![String::from("a1_a7"), String::from("d7_c6")],
+                ..FightMove::new(String::from("a1_a2"))
+            },
+            FightMove {
+                annotation: Some(Annotation::Blunder),
+                variation: vec![String::from("d7_e6")],
+                ..FightMove::new(String::from("d7_c7"))
+            },
+            FightMove::new(String::from("a2_a7")),
+        ],
+        final_sfen: String::from("8/R1k5/8/8/8/8/8/4K3 b - 3"),
+        result: GameResult::Draw,
+    };
+    let text = record.to_string();
+    assert!(text.contains("[Seed \"7\"]"), "{text}");
+    // Long movetext is wrapped.
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    assert!(
+        text.contains("1... e8_d7 {[%eval -0.35] [%clk 1:02:03] Only move} 2. a1_a2?"),
+        "{text}"
+    );
+    assert!(text.contains("(2. a1_a7 d7_c6)"), "{text}");
+    assert!(text.contains("d7_c7?? (2... d7_e6) 3. a2_a7"), "{text}");
+    assert_eq!(record.to_string().parse::<GameRecord>(), Ok(record));
+}
+
+#[test]
+fn games_with_shop_and_deploy_moves_round_trip() {
+    let mut first = GameRecord::new(Variant::ShuuroMini, "6/6/6/6/6/6 w - 1");
+    first.shop_moves = vec![String::from("+Q"), String::from("+q")];
+    first.deploy_moves = vec![String::from("K@c1"), String::from("k@c6")];
+    first.fight_sfen.clear();
+    first.result = GameResult::Aborted;
+    let mut second = fight("4k3/8/8/8/8/8/8/R3K3 w - 1", &["a1_a7"]);
+    second.seed = 3;
+    second.result = GameResult::WhiteWins;
+
+    let text = format!("{first}\n{second}");
+    assert_eq!(GameRecord::parse_all(&text), Ok(vec![first, second]));
+}
+
+#[test]
+fn replay_plays_legal_games() {
+    let mut record = fight("4k3/8/8/8/8/8/8/R3K3 w - 1", &["a1_a7", "e8_d8", "e1_e2"]);
+    replay(&mut record).unwrap();
+    assert_eq!(record.final_sfen, "3k4/R7/8/8/8/8/4K3/8 b - 4");
+}
+
+#[test]
+fn replay_rejects_illegal_moves() {
+    let mut record = fight("4k3/8/8/8/8/8/8/R3K3 w - 1", &["a1_a7", "e8_d8", "a7_b5"]);
+    assert_eq!(
+        replay(&mut record).unwrap_err(),
+        RecordError::IllegalMove(String::from("a7_b5"))
+    );
+}
+
+#[test]
+fn replay_rejects_moving_a_pinned_piece() {
+    let mut record = fight("4k3/4r3/8/8/8/8/4B3/4K3 w - 1", &["e2_d3"]);
+    assert_eq!(
+        replay(&mut record).unwrap_err(),
+        RecordError::IllegalMove(String::from("e2_d3"))
+    );
+}
@@ -0,0 +1,99 @@
+use shuuro::shuuro6::{bitboard6::BB6, square6::Square6};
+use shuuro::shuuro8::{bitboard8::BB8, square8::Square8};
+use shuuro::shuuro12::{bitboard12::BB12, square12::Square12};
+use shuuro_engine::{
+    EngineDefs,
+    engine6::search::Defs6,
+    engine8::search::Defs8,
+    engine12::search::Defs12,
+    imbalance::{ImbalanceTable, Sample},
+};
+
+// K Q R B N P C A G
+const QUEEN: [u32; 9] = [1, 1, 0, 0, 0, 0, 0, 0, 0];
+const THREE_KNIGHTS: [u32; 9] = [1, 0, 0, 0, 3, 0, 0, 0, 0];
+const KING: [u32; 9] = [1, 0, 0, 0, 0, 0, 0, 0, 0];
+
+fn tables() -> [&'static ImbalanceTable; 3] {
+    [
+        <Defs6 as EngineDefs<Square6, BB6<Square6>, 6>>::imbalance_table(),
+        <Defs8 as EngineDefs<Square8, BB8<Square8>, 8>>::imbalance_table(),
+        <Defs12 as EngineDefs<Square12, BB12<Square12>, 12>>::imbalance_table(),
+    ]
+}
+
+fn with(mut counts: [u32; 9], index: usize, count: u32) -> [u32; 9] {
+    counts[index] += count;
+    counts
+}
+
+#[test]
+fn second_rook_and_queen_are_redundant() {
+    for table in tables() {
+        assert!(table.evaluate(&[with(KING, 2, 2), KING]) < 0);
+        assert!(table.evaluate(&[with(QUEEN, 2, 1), KING]) < 0);
+    }
+}
+
+#[test]
+fn imbalance_is_colour_symmetric() {
+    let armies = [
+        QUEEN,
+        THREE_KNIGHTS,
+        [1, 1, 2, 2, 2, 8, 0, 0, 0],
+        [1, 0, 1, 0, 1, 6, 1, 2, 1],
+    ];
+    for table in tables() {
+        for white in armies {
+            for black in armies {
+                assert_eq!(
+                    table.evaluate(&[white, black]),
+                    -table.evaluate(&[black, white])
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn table_round_trips_through_text() {
+    for table in tables() {
+        let text = table.to_string();
+        assert_eq!(text.parse::<ImbalanceTable>(), Ok(*table));
+    }
+    assert!("1 2 3".parse::<ImbalanceTable>().is_err());
+}
+
+#[test]
+fn tuning_finds_a_planted_knight_pair_bonus() {
+    let zero = ImbalanceTable {
+        ours: [[0; 9]; 9],
+        theirs: [[0; 9]; 9],
+    };
+    let mut planted = zero;
+    planted.ours[4][4] = 40;
+    let mut samples = vec![];
+    for white in 0..4 {
+        for black in 0..4 {
+            for pawns in 0..3 {
+                let counts = [with(with(KING, 4, white), 5, pawns), with(KING, 4, black)];
+                // Knights are worth nothing but their pairs.
+                let eval = -60 * (white as i32 - black as i32);
+                let result = match (eval + planted.evaluate(&counts)).signum() {
+                    1 => 1.0,
+                    0 => 0.5,
+                    _ => 0.0,
+                };
+                samples.push(Sample {
+                    counts,
+                    eval,
+                    result,
+                });
+            }
+        }
+    }
+    let scale = 0.01;
+    let tuned = zero.tune(&samples, scale, 300);
+    assert!(tuned.error(&samples, scale) < zero.error(&samples, scale));
+    assert!(tuned.ours[4][4] > 0, "{}", tuned.ours[4][4]);
+}
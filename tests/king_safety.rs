@@ -0,0 +1,64 @@
+use shuuro::{
+    Color,
+    bitboard::BitBoard,
+    position::{Board, Play},
+    shuuro6::position6::P6,
+    shuuro8::{bitboard8::BB8, position8::P8, square8::Square8},
+};
+use shuuro_engine::{Engine, engine6::search::Engine6, engine8::search::Engine8};
+
+// Same material in both, only Black's queen, rook and knight moved next to
+// the white king.
+const QUIET: &str = "r1n1k3/pppq1ppp/8/8/8/8/PPP2PPP/3QRRK1 w - 1";
+const ATTACK: &str = "4k3/ppp2ppp/8/8/6nq/7r/PPP2PPP/3QRRK1 w - 1";
+
+fn position(sfen: &str) -> P8<Square8, BB8<Square8>> {
+    let mut position = P8::new();
+    position.set_sfen(sfen).unwrap();
+    position
+}
+
+#[test]
+fn king_attack_is_penalised() {
+    Engine8::init();
+    let engine = Engine8::new();
+    let quiet = position(QUIET);
+    let attack = position(ATTACK);
+
+    assert_eq!(engine.king_attackers_penalty(&quiet, Color::White), 0);
+    assert!(engine.king_attackers_penalty(&attack, Color::White) > 0);
+}
+
+#[test]
+fn mating_attack_scores_worse_for_defender() {
+    Engine8::init();
+    let engine = Engine8::new();
+    let king_safety = |sfen| {
+        let position = position(sfen);
+        let counts = [
+            engine.count_material(&position, Color::White),
+            engine.count_material(&position, Color::Black),
+        ];
+        let phase = engine.calculate_game_phase(&counts);
+        engine.king_safety_evaluation(&position, phase)
+    };
+    let quiet = king_safety(QUIET);
+    let attack = king_safety(ATTACK);
+    assert!(
+        attack < quiet,
+        "attack {attack} should be below quiet {quiet}"
+    );
+}
+
+#[test]
+fn king_zone_stops_at_top_edge_on_6x6() {
+    Engine6::init();
+    let engine = Engine6::new();
+    let mut position = P6::new();
+    position.set_sfen("6/2K3/6/6/3k2/6 w - 1").unwrap();
+
+    // The eight squares around the king and the king itself; the rank two in
+    // front of it is off the board.
+    assert_eq!(engine.king_zone(&position, Color::White).len(), 9);
+    assert_eq!(engine.king_attackers_penalty(&position, Color::White), 0);
+}
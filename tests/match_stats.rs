@@ -0,0 +1,65 @@
+use shuuro_engine::match_runner::{MatchStats, Sprt, Verdict};
+
+fn stats(wins: u64, draws: u64, losses: u64) -> MatchStats {
+    MatchStats {
+        wins,
+        draws,
+        losses,
+    }
+}
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!((actual - expected).abs() < 1e-6, "{actual} != {expected}");
+}
+
+#[test]
+fn elo_and_margin() {
+    let (elo, margin) = stats(10, 20, 10).elo();
+    assert_close(elo, 0.0);
+    assert_close(margin, 77.390213);
+
+    let (elo, margin) = stats(60, 20, 20).elo();
+    assert_close(elo, 147.190714);
+    assert_close(margin, 66.014639);
+
+    let (elo, margin) = stats(200, 200, 600).elo();
+    assert_close(elo, -147.190714);
+    assert_close(margin, 20.544230);
+
+    assert_eq!(stats(0, 0, 0).elo(), (0.0, 0.0));
+}
+
+#[test]
+fn llr_and_verdict() {
+    let sprt = Sprt::default();
+    assert_close(stats(60, 20, 20).llr(&sprt), 0.883207);
+    assert_eq!(stats(60, 20, 20).verdict(&sprt), Verdict::Continue);
+
+    assert_close(stats(600, 200, 200).llr(&sprt), 8.832073);
+    assert_eq!(stats(600, 200, 200).verdict(&sprt), Verdict::H1);
+
+    assert_close(stats(200, 200, 600).llr(&sprt), -9.155631);
+    assert_eq!(stats(200, 200, 600).verdict(&sprt), Verdict::H0);
+}
+
+#[test]
+fn llr_without_losses() {
+    let sprt = Sprt::default();
+    assert_close(stats(10, 5, 0).llr(&sprt), 0.640569);
+    assert_close(stats(30, 10, 0).llr(&sprt), 2.280338);
+    assert_eq!(stats(60, 20, 0).verdict(&sprt), Verdict::H1);
+}
+
+#[test]
+fn identical_results_have_no_llr() {
+    let sprt = Sprt::default();
+    for stats in [
+        stats(0, 0, 0),
+        stats(8, 0, 0),
+        stats(0, 8, 0),
+        stats(0, 0, 8),
+    ] {
+        assert_eq!(stats.llr(&sprt), 0.0, "{stats}");
+        assert_eq!(stats.verdict(&sprt), Verdict::Continue);
+    }
+}
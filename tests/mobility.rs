@@ -0,0 +1,27 @@
+use shuuro::{
+    position::{Board, Play},
+    shuuro8::position8::P8,
+};
+use shuuro_engine::{Engine, engine8::search::Engine8};
+
+// Knights on c3 and f3 don't reach each other's squares or the kings'.
+const NONE: &str = "k6K/8/8/8/8/8/8/8 w - 1";
+const ONE: &str = "k6K/8/8/8/8/2N5/8/8 w - 1";
+const TWO: &str = "k6K/8/8/8/8/2N2N2/8/8 w - 1";
+
+#[test]
+fn mobility_adds_up_over_pieces_of_one_type() {
+    Engine8::init();
+    let engine = Engine8::new();
+    let mobility = |sfen| {
+        let mut position = P8::new();
+        position.set_sfen(sfen).unwrap();
+        // Same phase everywhere, so the same weights apply.
+        engine.mobility_evaluation(&position, 0)
+    };
+    let none = mobility(NONE);
+    let one = mobility(ONE) - none;
+    let two = mobility(TWO) - none;
+    assert!(one > 0);
+    assert_eq!(two, 2 * one);
+}
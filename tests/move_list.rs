@@ -0,0 +1,23 @@
+use shuuro::{
+    Color,
+    attacks::Attacks,
+    position::Play,
+    shuuro12::{attacks12::Attacks12, bitboard12::BB12, position12::P12, square12::Square12},
+};
+use shuuro_engine::move_list::MoveList;
+
+type Position = P12<Square12, BB12<Square12>>;
+type Attack12 = Attacks12<Square12, BB12<Square12>>;
+
+#[test]
+fn two_dozen_queens_fit_in_a_move_list() {
+    Attack12::init();
+    // 24 queens, as a 12x12 army can have after promotions, with 628 moves.
+    let mut position = Position::default();
+    position
+        .set_sfen("kb2Q1Q3Q1/ppQ5Q3/Q4Q6/3Q7Q/1Q7Q2/7Q4/5Q4Q1/1Q1Q6Q1/6Q5/Q8Q2/2Q8Q/4Q3Q2K w - 1")
+        .unwrap();
+    let moves = MoveList::pseudo_legal(&position, Color::White);
+    assert_eq!(moves.len(), 628);
+    assert_eq!(moves.iter().count(), moves.len());
+}
@@ -0,0 +1,143 @@
+use shuuro::{
+    Variant,
+    position::{Board, Play},
+    shuuro6::{bitboard6::BB6, position6::P6, square6::Square6},
+};
+use shuuro_engine::{
+    EngineDefs,
+    engine6::search::Defs6,
+    move_list::MoveList,
+    nnue::{L2, L3, Network, Nnue, PIECE_KINDS, simd},
+    prng::Prng,
+};
+use std::{fs, path::PathBuf, sync::Arc};
+
+const SQUARES: usize = 36;
+const HIDDEN: usize = 64;
+
+/// Writes a network with small random weights for the 6x6 board.
+fn random_network(name: &str) -> PathBuf {
+    let mut prng = Prng::new(7);
+    let mut small = |range: u64| (prng.next_u64() % (2 * range + 1)) as i64 - range as i64;
+    let features = SQUARES * PIECE_KINDS * SQUARES;
+    let mut bytes = b"SNUE".to_vec();
+    for value in [1, SQUARES, HIDDEN, L2, L3] {
+        bytes.extend((value as u32).to_le_bytes());
+    }
+    for _ in 0..HIDDEN + features * HIDDEN {
+        bytes.extend((small(40) as i16).to_le_bytes());
+    }
+    for (bias, weights) in [(L2, L2 * 2 * HIDDEN), (L3, L3 * L2), (1, L3)] {
+        for _ in 0..bias {
+            bytes.extend((small(500) as i32).to_le_bytes());
+        }
+        bytes.extend((0..weights).map(|_| small(60) as i8 as u8));
+    }
+    let path = std::env::temp_dir().join(format!("shuuro-engine-{name}.nnue"));
+    fs::write(&path, bytes).unwrap();
+    path
+}
+
+fn refreshed(network: &Arc<Network>, position: &P6<Square6, BB6<Square6>>) -> Nnue {
+    let mut nnue = Nnue::new(network.clone());
+    nnue.refresh(position);
+    nnue
+}
+
+#[test]
+fn incremental_updates_match_refresh() {
+    let path = random_network("incremental");
+    let network = Arc::new(Network::load(&path, SQUARES).unwrap());
+    fs::remove_file(path).unwrap();
+    let mut prng = Prng::new(3);
+
+    for sfen in Defs6::bench_positions() {
+        let mut root = P6::new();
+        root.update_variant(Variant::ShuuroMiniFairy);
+        root.set_sfen(sfen).unwrap();
+        let mut nnue = refreshed(&network, &root);
+        let mut line = vec![root];
+
+        // Captures are played whenever there are some and king moves half of
+        // the time, so every kind of update is made along the way.
+        for _ in 0..10 {
+            let parent = line.last().unwrap();
+            let stm = parent.side_to_move();
+            let moves = MoveList::legal(parent, stm);
+            let king = parent.find_king(stm).unwrap();
+            let captures: Vec<_> = moves
+                .iter()
+                .filter(|mv| {
+                    mv.info()
+                        .is_some_and(|(_, to)| parent.piece_at(to).is_some())
+                })
+                .collect();
+            let king_moves: Vec<_> = moves
+                .iter()
+                .filter(|mv| mv.info().is_some_and(|(from, _)| from == king))
+                .collect();
+            let candidates = if !captures.is_empty() {
+                captures
+            } else if !king_moves.is_empty() && prng.below(2) == 0 {
+                king_moves
+            } else {
+                moves.iter().collect()
+            };
+            if candidates.is_empty() {
+                break;
+            }
+            let mv = candidates[prng.below(candidates.len())].clone();
+            let mut child = parent.clone();
+            let over = child.make_move(mv.clone()).is_err();
+            nnue.push(parent, &child, &mv);
+            let fresh = refreshed(&network, &child);
+            assert_eq!(nnue.accumulator(), fresh.accumulator(), "{sfen} {mv}");
+            assert_eq!(
+                nnue.evaluate(child.side_to_move()),
+                fresh.evaluate(child.side_to_move())
+            );
+            line.push(child);
+            if over {
+                break;
+            }
+        }
+
+        while line.len() > 1 {
+            line.pop();
+            nnue.pop();
+            let fresh = refreshed(&network, line.last().unwrap());
+            assert_eq!(nnue.accumulator(), fresh.accumulator(), "{sfen}");
+        }
+    }
+}
+
+#[test]
+fn kernels_match_scalar() {
+    let mut prng = Prng::new(11);
+    for len in [32, 64, 256, 1024] {
+        let acc: Vec<i16> = (0..len).map(|_| prng.next_u64() as i16).collect();
+        let weights: Vec<i16> = (0..len).map(|_| prng.next_u64() as i16).collect();
+        for (kernel, reference) in [
+            (
+                simd::add_assign as fn(&mut [i16], &[i16]),
+                simd::scalar::add_assign as fn(&mut [i16], &[i16]),
+            ),
+            (simd::sub_assign, simd::scalar::sub_assign),
+        ] {
+            let mut fast = acc.clone();
+            let mut slow = acc.clone();
+            kernel(&mut fast, &weights);
+            reference(&mut slow, &weights);
+            assert_eq!(fast, slow, "{len}");
+        }
+
+        // Inputs are clipped activations.
+        let input: Vec<u8> = (0..len).map(|_| (prng.next_u64() % 128) as u8).collect();
+        let weights: Vec<i8> = (0..len).map(|_| prng.next_u64() as i8).collect();
+        assert_eq!(
+            simd::dot(&input, &weights),
+            simd::scalar::dot(&input, &weights),
+            "{len}"
+        );
+    }
+}
@@ -0,0 +1,127 @@
+use shuuro::{
+    Move, Square, Variant,
+    attacks::Attacks,
+    bitboard::BitBoard,
+    position::{Board, Placement, Play, Rules, Sfen},
+};
+use shuuro_engine::{
+    engine::{Engine, EngineDefs, with_stack},
+    move_list::MoveList,
+    on_board,
+};
+use std::{fmt::Display, hash::Hash};
+
+/// Perft over the crate's own legal moves, skipping the ones that leave the
+/// king in check as the search always has.
+fn reference<S, B, A, P>(position: &P, depth: u32) -> u64
+where
+    S: Square + Hash,
+    B: BitBoard<S>,
+    A: Attacks<S, B>,
+    P: Clone + Board<S, B, A> + Sfen<S, B, A> + Placement<S, B, A> + Play<S, B, A> + Rules<S, B, A>,
+{
+    if depth == 0 {
+        return 1;
+    }
+    let stm = position.side_to_move();
+    let mut nodes = 0;
+    for (from, targets) in position.legal_moves(stm) {
+        for to in targets {
+            let mut child = position.clone();
+            nodes += match child.make_move(Move::new(from, to)) {
+                _ if child.in_check(stm) => 0,
+                Ok(_) => reference(&child, depth - 1),
+                Err(_) => u64::from(depth == 1),
+            };
+        }
+    }
+    nodes
+}
+
+fn assert_perft<
+    E,
+    S,
+    B,
+    A,
+    P,
+    D,
+    const FILE: usize,
+    const BITBOARD_SIZE: usize,
+    const RANK: usize,
+>(
+    variants: [Variant; 2],
+    depth: u32,
+) where
+    E: Engine<S, B, A, P, D, FILE, BITBOARD_SIZE, RANK>,
+    S: Square + Hash + Send + 'static,
+    B: BitBoard<S>,
+    A: Attacks<S, B>,
+    P: Sized
+        + Display
+        + Clone
+        + Board<S, B, A>
+        + Sfen<S, B, A>
+        + Placement<S, B, A>
+        + Play<S, B, A>
+        + Rules<S, B, A>
+        + Send
+        + 'static,
+    D: EngineDefs<S, B, FILE>,
+{
+    E::init();
+    let engine = E::new();
+    for variant in variants {
+        for sfen in D::bench_positions() {
+            let mut position = P::new();
+            position.update_variant(variant);
+            position.set_sfen(sfen).unwrap();
+            let legal = MoveList::legal(&position, position.side_to_move());
+            let pseudo = MoveList::pseudo_legal(&position, position.side_to_move());
+            for mv in legal.iter() {
+                let (from, to) = mv.info().unwrap();
+                assert!(
+                    pseudo.contains(from, to),
+                    "{variant:?} {sfen}: {mv} missing"
+                );
+            }
+            for depth in 1..=depth {
+                assert_eq!(
+                    engine.perft(&position, depth),
+                    reference(&position, depth),
+                    "{variant:?} {sfen} depth {depth}"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn shuuro_mini_perft_matches_legal_moves() {
+    with_stack(|| {
+        on_board!(
+            6,
+            assert_perft,
+            [Variant::ShuuroMini, Variant::ShuuroMiniFairy],
+            3
+        );
+    });
+}
+
+#[test]
+fn standard_perft_matches_legal_moves() {
+    with_stack(|| {
+        on_board!(
+            8,
+            assert_perft,
+            [Variant::Standard, Variant::StandardFairy],
+            3
+        );
+    });
+}
+
+#[test]
+fn shuuro_perft_matches_legal_moves() {
+    with_stack(|| {
+        on_board!(12, assert_perft, [Variant::Shuuro, Variant::ShuuroFairy], 2);
+    });
+}
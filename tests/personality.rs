@@ -0,0 +1,56 @@
+use shuuro::{
+    Variant,
+    position::{Board, Play},
+    shuuro8::position8::P8,
+};
+use shuuro_engine::{
+    Engine,
+    engine8::search::Engine8,
+    personality::{self, PERSONALITIES, Personality},
+};
+
+#[test]
+fn combo_lists_every_personality() {
+    let option = personality::uci_option();
+    assert!(
+        option.starts_with("option name Personality type combo default Default "),
+        "{option}"
+    );
+    let choices: Vec<&str> = option.split(" var ").skip(1).collect();
+    let names: Vec<&str> = PERSONALITIES.iter().map(|p| p.name).collect();
+    assert_eq!(choices, names);
+}
+
+#[test]
+fn names_match_in_any_case() {
+    for personality in &PERSONALITIES {
+        for name in [
+            personality.name.to_string(),
+            personality.name.to_lowercase(),
+            personality.name.to_uppercase(),
+        ] {
+            assert_eq!(Personality::by_name(&name), Some(personality));
+        }
+    }
+    assert_eq!(Personality::by_name("Reckless"), None);
+}
+
+#[test]
+fn personalities_change_the_evaluation() {
+    // White has a Chancellor and a free file, Black the queen.
+    let mut position = P8::new();
+    position.update_variant(Variant::StandardFairy);
+    position
+        .set_sfen("r1bqkb1r/pppp1ppp/2n2n2/3_.4/4_.3/2N2N2/PPP2PPP/R1BCKB1R w - 1")
+        .unwrap();
+    Engine8::init();
+    let evaluate = |name| {
+        let mut engine = Engine8::new();
+        engine.set_option("Personality", name);
+        engine.evaluate_position(&position)
+    };
+
+    let default = evaluate("Default");
+    assert!(evaluate("FairyLover") > default);
+    assert_ne!(evaluate("aggressive"), default);
+}
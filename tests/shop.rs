@@ -0,0 +1,88 @@
+use shuuro::{
+    Color, Move, PieceType, Selection, Square, Variant, shuuro6::square6::Square6,
+    shuuro8::square8::Square8, shuuro12::square12::Square12,
+};
+use shuuro_engine::shop::{ShopAdvisor, ShopStrategy, price};
+
+const STRATEGIES: [ShopStrategy; 3] = [
+    ShopStrategy::Balanced,
+    ShopStrategy::Aggressive,
+    ShopStrategy::PawnHeavy,
+];
+
+/// Most pieces of each type a side may buy, indexed by `PieceType`.
+fn limits(variant: Variant) -> [u8; 9] {
+    let pawns = match variant {
+        Variant::ShuuroMini | Variant::ShuuroMiniFairy => 8,
+        Variant::Standard | Variant::StandardFairy => 12,
+        _ => 18,
+    };
+    [1, 3, 6, 9, 9, pawns, 3, 3, 4]
+}
+
+fn assert_within_limits<S: Square>(variants: [Variant; 2]) {
+    for variant in variants {
+        for strategy in STRATEGIES {
+            for color in [Color::White, Color::Black] {
+                let purchases = ShopAdvisor::new(variant, strategy).purchases::<S>(color);
+                let mut counts = [0; 9];
+                let mut spent = 0;
+                for mv in &purchases {
+                    let Move::Select { piece } = mv else {
+                        panic!("{variant:?} {strategy:?}: {mv} is not a purchase");
+                    };
+                    assert_eq!(piece.color, color);
+                    assert!(variant.can_select(&piece.piece_type), "{variant:?} {mv}");
+                    counts[piece.piece_type.index()] += 1;
+                    spent += price::<S>(variant, piece.piece_type);
+                }
+                assert!(
+                    spent <= variant.start_credit(),
+                    "{variant:?} {strategy:?}: spent {spent}"
+                );
+                for (count, limit) in counts.iter().zip(limits(variant)) {
+                    assert!(*count <= limit, "{variant:?} {strategy:?}: {counts:?}");
+                }
+
+                // The crate's own shop takes every purchase.
+                let mut selection = Selection::<S>::default();
+                selection.update_variant(variant);
+                for mv in purchases {
+                    assert!(selection.play(mv.clone()).is_some(), "{variant:?} {mv}");
+                }
+                assert_eq!(
+                    selection.credit(color),
+                    variant.start_credit() - spent,
+                    "{variant:?} {strategy:?}"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn shuuro_mini_armies_stay_within_limits() {
+    assert_within_limits::<Square6>([Variant::ShuuroMini, Variant::ShuuroMiniFairy]);
+}
+
+#[test]
+fn standard_armies_stay_within_limits() {
+    assert_within_limits::<Square8>([Variant::Standard, Variant::StandardFairy]);
+}
+
+#[test]
+fn shuuro_armies_stay_within_limits() {
+    assert_within_limits::<Square12>([Variant::Shuuro, Variant::ShuuroFairy]);
+}
+
+#[test]
+fn prices_come_from_the_shop() {
+    assert_eq!(price::<Square12>(Variant::Shuuro, PieceType::Queen), 110);
+    assert_eq!(
+        price::<Square12>(Variant::ShuuroFairy, PieceType::Chancellor),
+        130
+    );
+    assert_eq!(price::<Square8>(Variant::Standard, PieceType::Pawn), 10);
+    // Not for sale without fairy pieces.
+    assert_eq!(price::<Square6>(Variant::ShuuroMini, PieceType::Giraffe), 0);
+}
@@ -0,0 +1,121 @@
+use shuuro::{Color, Move, Square, shuuro8::square8::Square8};
+use shuuro_engine::{
+    engine::SearchLimits,
+    skill::{MAX_ELO, MAX_LEVEL, MIN_ELO, SkillCaps, SkillState},
+};
+use std::time::Duration;
+
+const CAPS: SkillCaps = SkillCaps {
+    depth: [1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 6, 6],
+    nodes: [
+        10, 20, 30, 40, 50, 60, 70, 80, 90, 100, 110, 120, 130, 140, 150, 160, 170, 180, 190, 200,
+    ],
+};
+
+fn level(level: u8) -> SkillState {
+    let mut state = SkillState::default();
+    state.level = level;
+    state
+}
+
+fn elo(elo: u32) -> SkillState {
+    let mut state = SkillState::default();
+    state.limit_strength = true;
+    state.elo = elo;
+    state
+}
+
+fn mv(to: &str) -> Move<Square8> {
+    Move::new(
+        Square8::from_sfen("a1").unwrap(),
+        Square8::from_sfen(to).unwrap(),
+    )
+}
+
+#[test]
+fn elo_maps_evenly_onto_levels() {
+    assert_eq!(elo(MIN_ELO).level(), 0);
+    assert_eq!(elo(1200).level(), 5);
+    assert_eq!(elo(1600).level(), 10);
+    assert_eq!(elo(2320).level(), 19);
+    assert_eq!(elo(MAX_ELO).level(), MAX_LEVEL);
+    assert_eq!(elo(500).level(), 0);
+    assert_eq!(elo(3000).level(), MAX_LEVEL);
+    assert!(!elo(MAX_ELO).enabled());
+
+    // Without `UCI_LimitStrength` the Elo is ignored.
+    let mut state = level(7);
+    state.elo = MIN_ELO;
+    assert_eq!(state.level(), 7);
+    assert_eq!(level(30).level(), MAX_LEVEL);
+}
+
+#[test]
+fn limits_are_capped_by_level() {
+    let capped = level(5).limits(SearchLimits::default(), &CAPS);
+    assert_eq!((capped.depth, capped.nodes), (Some(2), Some(60)));
+
+    let limits = SearchLimits {
+        depth: Some(1),
+        nodes: Some(25),
+        movetime: Some(Duration::from_millis(100)),
+    };
+    let capped = level(5).limits(limits.clone(), &CAPS);
+    assert_eq!((capped.depth, capped.nodes), (Some(1), Some(25)));
+    assert_eq!(capped.movetime, limits.movetime);
+
+    let full = level(MAX_LEVEL).limits(limits.clone(), &CAPS);
+    assert_eq!((full.depth, full.nodes), (limits.depth, limits.nodes));
+    assert_eq!(
+        level(MAX_LEVEL)
+            .limits(SearchLimits::default(), &CAPS)
+            .nodes,
+        None
+    );
+}
+
+#[test]
+fn pick_favours_better_moves() {
+    let counts = |state: &mut SkillState, candidates: &[(Move<Square8>, i32)], player| {
+        let mut counts = [0; 3];
+        for _ in 0..2000 {
+            let (picked, _) = state.pick(candidates, player).unwrap();
+            let index = candidates.iter().position(|(mv, _)| *mv == picked);
+            counts[index.unwrap()] += 1;
+        }
+        counts
+    };
+
+    // Weights fall off as exp(-loss / (10 + 5 * (20 - level))).
+    let white = [(mv("a2"), 110), (mv("a3"), 0), (mv("a4"), -500)];
+    let [best, worse, worst] = counts(&mut level(0), &white, Color::White);
+    let share = best as f64 / 2000.0;
+    assert!((0.68..0.78).contains(&share), "{best} {worse} {worst}");
+    assert!(worst < 20, "{worst}");
+    assert_eq!(counts(&mut level(19), &white, Color::White), [2000, 0, 0]);
+
+    // Black's best has the lowest score.
+    let black = [(mv("a2"), -110), (mv("a3"), 0), (mv("a4"), 500)];
+    let [best, worse, _] = counts(&mut level(0), &black, Color::Black);
+    assert!(best > 2 * worse, "{best} {worse}");
+
+    let equal = [(mv("a2"), 0), (mv("a3"), 0), (mv("a4"), 0)];
+    for count in counts(&mut level(10), &equal, Color::White) {
+        assert!((550..780).contains(&count), "{count}");
+    }
+    assert!(level(0).pick::<Square8>(&[], Color::White).is_none());
+}
+
+#[test]
+fn noise_amplitude_shrinks_with_level() {
+    for lvl in [0, 10, 19] {
+        let mut state = level(lvl);
+        state.new_search();
+        let amplitude = 8 * (MAX_LEVEL - lvl) as i32;
+        let noise: Vec<i32> = (0..5000).map(|key| state.noise(key)).collect();
+        assert!(noise.iter().all(|n| n.abs() <= amplitude), "{lvl}");
+        assert!(noise.iter().any(|n| n.abs() >= amplitude * 9 / 10), "{lvl}");
+        assert_eq!(state.noise(42), state.noise(42));
+    }
+    assert!((0..1000).all(|key| level(MAX_LEVEL).noise(key) == 0));
+}
@@ -0,0 +1,89 @@
+use shuuro::{
+    position::{Board, Play},
+    shuuro6::{bitboard6::BB6, position6::P6, square6::Square6},
+    shuuro8::{bitboard8::BB8, position8::P8, square8::Square8},
+    shuuro12::{bitboard12::BB12, position12::P12, square12::Square12},
+};
+use shuuro_engine::{
+    Engine, EngineDefs,
+    engine::mirror_sfen,
+    engine6::search::{Defs6, Engine6},
+    engine8::search::{Defs8, Engine8},
+    engine12::search::{Defs12, Engine12},
+};
+
+// No Giraffes: the crate's 6x6 Giraffe attacks are not mirror-symmetric
+// (e1 reaches three squares, e6 none), so the rules themselves differ.
+const CORPUS6: [&str; 2] = [
+    "1ka1n1/pp1p1p/1_.4/4_C1/P1P1PP/1NK1Q1 w - 1",
+    "3k2/6/1K_.3/_.5/P5/6 b - 37",
+];
+const CORPUS8: [&str; 4] = [
+    "r1n1k3/pppq1ppp/8/8/8/8/PPP2PPP/3QRRK1 w - 1",
+    "4k3/ppp2ppp/8/8/6nq/7r/PPP2PPP/3QRRK1 w - 1",
+    "2c1k3/pp1a1ppp/2_.5/3_N4/8/2_.5/PPP2PPP/2G1K1A1 b - 1",
+    "8/1P1p3P/3_N1_.2/4B3/4_.3/_.3Q3/2K3k1/6R1 b - 77",
+];
+const CORPUS12: [&str; 2] = [
+    "1_nb2n1k2q1/p1n2p_.1p3/9_.2/4_.7/12/12/_.11/12/2_.9/12/P3P4q2/2BKN2R_N1_N1 w - 60",
+    "1_n3rkq1qcn/p1p1pp_Cpppp1/Q8_a1p/4_.7/12/5b6/_.7b3/12/2_.9/5Q2P3/PPPAP2P1PPP/1B1KBR2_N1_N1 b - 51",
+];
+
+fn assert_symmetric(sfen: &str, eval: impl Fn(&str) -> i32) {
+    let mirrored = mirror_sfen(sfen).unwrap();
+    assert_eq!(mirror_sfen(&mirrored).as_deref(), Some(sfen));
+    assert_eq!(eval(sfen), -eval(&mirrored), "{sfen} / {mirrored}");
+}
+
+#[test]
+fn mirror_swaps_colours_and_flips_ranks() {
+    assert_eq!(
+        mirror_sfen("4k3/8/_.7/8/8/8/3_N4/4K3 w qR 1").as_deref(),
+        Some("4k3/3_n4/8/8/8/_.7/8/4K3 b Qr 1")
+    );
+}
+
+#[test]
+fn evaluation_is_symmetric_6x6() {
+    Engine6::init();
+    let engine = Engine6::new();
+    let eval = |sfen: &str| {
+        let mut position = P6::<Square6, BB6<Square6>>::new();
+        position.set_sfen(sfen).unwrap();
+        engine.evaluate_position(&position)
+    };
+    let start = <Defs6 as EngineDefs<Square6, BB6<Square6>, 6>>::start_positions();
+    for sfen in start.iter().chain(&CORPUS6) {
+        assert_symmetric(sfen, eval);
+    }
+}
+
+#[test]
+fn evaluation_is_symmetric_8x8() {
+    Engine8::init();
+    let engine = Engine8::new();
+    let eval = |sfen: &str| {
+        let mut position = P8::<Square8, BB8<Square8>>::new();
+        position.set_sfen(sfen).unwrap();
+        engine.evaluate_position(&position)
+    };
+    let start = <Defs8 as EngineDefs<Square8, BB8<Square8>, 8>>::start_positions();
+    for sfen in start.iter().chain(&CORPUS8) {
+        assert_symmetric(sfen, eval);
+    }
+}
+
+#[test]
+fn evaluation_is_symmetric_12x12() {
+    Engine12::init();
+    let engine = Engine12::new();
+    let eval = |sfen: &str| {
+        let mut position = P12::<Square12, BB12<Square12>>::new();
+        position.set_sfen(sfen).unwrap();
+        engine.evaluate_position(&position)
+    };
+    let start = <Defs12 as EngineDefs<Square12, BB12<Square12>, 12>>::start_positions();
+    for sfen in start.iter().chain(&CORPUS12) {
+        assert_symmetric(sfen, eval);
+    }
+}
@@ -0,0 +1,160 @@
+use shuuro::{
+    Color, PieceType, Square,
+    attacks::Attacks,
+    bitboard::BitBoard,
+    position::{Board, Play},
+    shuuro6::{attacks6::Attacks6, bitboard6::BB6, position6::P6, square6::Square6},
+};
+use shuuro_engine::{
+    move_list::MoveList,
+    tablebase::{Generator, Probe, Table, Tablebase, Wdl},
+};
+use std::{fs, io};
+
+fn generate(signature: &str) -> Generator {
+    Attacks6::init();
+    let mut generator = Generator::new(BB6::empty());
+    generator.generate(&signature.parse().unwrap());
+    generator
+}
+
+fn table<'a>(generator: &'a Generator, signature: &str) -> &'a Table {
+    generator
+        .tables()
+        .find(|table| table.signature().to_string() == signature)
+        .unwrap()
+}
+
+fn position(sfen: &str) -> P6<Square6, BB6<Square6>> {
+    let mut position = P6::new();
+    position.set_sfen(sfen).unwrap();
+    position
+}
+
+#[test]
+fn queen_and_rook_win_everywhere() {
+    for (signature, max_dtm) in [("KQvK", 14), ("KRvK", 24)] {
+        let generator = generate(signature);
+        let table = table(&generator, signature);
+        assert!(
+            table
+                .results(Color::White)
+                .all(|probe| probe.wdl == Wdl::Win),
+            "{signature}"
+        );
+        assert!(
+            table
+                .results(Color::Black)
+                .all(|probe| probe.wdl != Wdl::Win),
+            "{signature}"
+        );
+        assert_eq!(table.max_dtm(), max_dtm, "{signature}");
+    }
+}
+
+#[test]
+fn bare_kings_and_lone_knight_draw() {
+    let generator = generate("KNvK");
+    for signature in ["KvK", "KNvK"] {
+        let table = table(&generator, signature);
+        for stm in [Color::White, Color::Black] {
+            assert!(
+                table.results(stm).all(|probe| probe.wdl == Wdl::Draw),
+                "{signature}"
+            );
+        }
+    }
+}
+
+#[test]
+fn tables_round_trip_and_probe_from_positions() {
+    let generator = generate("KRvK");
+    let dir = std::env::temp_dir().join("shuuro-engine-tablebase");
+    assert_eq!(generator.write(&dir).unwrap(), 2);
+    let tablebase = Tablebase::load(&dir).unwrap();
+    let written = table(&generator, "KRvK");
+    let read = Table::read(dir.join("KRvK-000000000.s6w")).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(tablebase.len(), 2);
+    assert_eq!(read.signature(), written.signature());
+    assert_eq!(read.plinths(), written.plinths());
+    for stm in [Color::White, Color::Black] {
+        assert!(read.results(stm).eq(written.results(stm)));
+    }
+
+    // Mate in one, mated, and a rook left hanging next to the king.
+    let mate_in_one = position("k5/2K3/6/6/6/1R4 w - 1");
+    let mated = position("k5/2K3/6/6/6/R5 b - 1");
+    let hanging = position("k5/R5/6/6/6/5K b - 1");
+    assert_eq!(
+        tablebase.probe(&mate_in_one),
+        Some(Probe {
+            wdl: Wdl::Win,
+            dtm: 1
+        })
+    );
+    assert_eq!(
+        tablebase.probe(&mated),
+        Some(Probe {
+            wdl: Wdl::Loss,
+            dtm: 0
+        })
+    );
+    assert_eq!(
+        tablebase.probe(&hanging),
+        Some(Probe {
+            wdl: Wdl::Draw,
+            dtm: 0
+        })
+    );
+}
+
+#[test]
+fn tables_of_an_older_version_are_rejected() {
+    let generator = generate("KRvK");
+    let dir = std::env::temp_dir().join("shuuro-engine-tablebase-version");
+    generator.write(&dir).unwrap();
+    let path = dir.join("KRvK-000000000.s6w");
+    let mut data = fs::read(&path).unwrap();
+    data[4] = 1;
+    fs::write(&path, data).unwrap();
+    let read = Table::read(&path);
+    fs::remove_dir_all(&dir).unwrap();
+    assert!(matches!(read, Err(e) if e.kind() == io::ErrorKind::InvalidData));
+}
+
+/// The crate's Giraffe on b3 reaches bit 36, past the board, and its legal
+/// moves stop there. Once the crate is fixed the first assertions fail.
+#[test]
+fn giraffe_moves_from_b3_stay_on_the_board() {
+    Attacks6::init();
+    let b3 = Square6::from_sfen("b3").unwrap();
+    let attacks = Attacks6::get_giraffe_attacks(&b3);
+    assert!(attacks.0 >> 36 != 0, "{:b}", attacks.0);
+
+    let giraffe_moves = |moves: MoveList<Square6>, position: &P6<Square6, BB6<Square6>>| {
+        let giraffe = position.type_bb(&PieceType::Giraffe);
+        moves
+            .iter()
+            .filter(|mv| {
+                mv.info()
+                    .is_some_and(|(from, _)| (giraffe & &from).is_any())
+            })
+            .count()
+    };
+    let a1 = position("k5/6/6/6/6/G4K w - 1");
+    assert_eq!(giraffe_moves(MoveList::legal(&a1, Color::White), &a1), 2);
+    let b3 = position("k5/6/6/1G4/6/5K w - 1");
+    assert_eq!(giraffe_moves(MoveList::legal(&b3, Color::White), &b3), 0);
+
+    // The engine's own moves leap to f2 and f4.
+    assert_eq!(
+        giraffe_moves(MoveList::pseudo_legal(&a1, Color::White), &a1),
+        2
+    );
+    assert_eq!(
+        giraffe_moves(MoveList::pseudo_legal(&b3, Color::White), &b3),
+        2
+    );
+}
@@ -0,0 +1,70 @@
+use shuuro_engine::{
+    engine::{Engine, SearchLimits, with_stack},
+    engine6::search::Engine6,
+    engine8::search::Engine8,
+    engine12::search::Engine12,
+    on_board,
+    testsuite::{self, EpdEntry, SuiteResult},
+};
+
+// Fixed depth keeps the suites independent of the machine's speed.
+const DEPTH: &str = "depth 3";
+
+fn entries(variant: &str) -> Vec<EpdEntry> {
+    EpdEntry::load(format!(
+        "{}/testsuites/{variant}.epd",
+        env!("CARGO_MANIFEST_DIR")
+    ))
+    .unwrap()
+}
+
+fn assert_solved(variant: &str, results: Vec<SuiteResult>) {
+    assert!(!results.is_empty(), "{variant}: empty suite");
+    for result in results {
+        assert!(
+            result.solved_in.is_some(),
+            "{variant}: {} failed with {:?}",
+            result.id,
+            result.best_move
+        );
+    }
+}
+
+#[test]
+fn shuuro_mini_suites_are_solved() {
+    with_stack(|| {
+        let limits = SearchLimits::parse(DEPTH);
+        for variant in ["shuuroMini", "shuuroMiniFairy"] {
+            Engine6::init();
+            let mut engine = Engine6::new();
+            let results = on_board!(6, testsuite::run, &mut engine, &entries(variant), &limits);
+            assert_solved(variant, results);
+        }
+    });
+}
+
+#[test]
+fn standard_suites_are_solved() {
+    with_stack(|| {
+        let limits = SearchLimits::parse(DEPTH);
+        for variant in ["standard", "standardFairy"] {
+            Engine8::init();
+            let mut engine = Engine8::new();
+            let results = on_board!(8, testsuite::run, &mut engine, &entries(variant), &limits);
+            assert_solved(variant, results);
+        }
+    });
+}
+
+#[test]
+fn shuuro_suites_are_solved() {
+    with_stack(|| {
+        let limits = SearchLimits::parse(DEPTH);
+        for variant in ["shuuro", "shuuroFairy"] {
+            Engine12::init();
+            let mut engine = Engine12::new();
+            let results = on_board!(12, testsuite::run, &mut engine, &entries(variant), &limits);
+            assert_solved(variant, results);
+        }
+    });
+}